@@ -0,0 +1,208 @@
+//! Project discovery and the defaults taken from the scripts a project declares. The
+//! installer includes this file with `#[path]`, in build.rs and to find the projects it
+//! installs, so it only depends on std and toml.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the project set by `project`; it runs `entry_point`.
+pub const MAIN_PROJECT: &str = "main";
+
+/// Console and GUI scripts a project declares in its pyproject.toml.
+#[derive(Debug, Default)]
+pub struct DeclaredScripts {
+    pub scripts: Vec<String>,
+    pub gui_scripts: Vec<String>,
+}
+
+impl DeclaredScripts {
+    pub fn read(project_dir: &Path) -> Result<Self, String> {
+        let path = project_dir.join("pyproject.toml");
+        let contents =
+            fs::read_to_string(&path).map_err(|err| format!("read {}: {err}", path.display()))?;
+        let doc: toml::Value =
+            toml::from_str(&contents).map_err(|err| format!("parse {}: {err}", path.display()))?;
+        let names = |table: &str| -> Vec<String> {
+            doc.get("project")
+                .and_then(|project| project.get(table))
+                .and_then(|t| t.as_table())
+                .map(|t| t.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            scripts: names("scripts"),
+            gui_scripts: names("gui-scripts"),
+        })
+    }
+
+    pub fn declares(&self, name: &str) -> bool {
+        self.scripts.iter().chain(&self.gui_scripts).any(|s| s == name)
+    }
+
+    pub fn is_gui(&self, name: &str) -> bool {
+        self.gui_scripts.iter().any(|s| s == name)
+    }
+}
+
+/// The script a `command:` target runs, if it is one.
+pub fn command_name(target: &str) -> Option<&str> {
+    let rest = target.trim().strip_prefix("command:")?;
+    rest.split_whitespace().next().map(|name| name.trim_matches('"'))
+}
+
+/// Validates `project` and `[projects]` and returns each project's directory by name.
+pub fn project_dirs(
+    project: &str,
+    projects: &BTreeMap<String, String>,
+    repo_root: &Path,
+) -> Result<BTreeMap<String, PathBuf>, String> {
+    let app_dir = repo_root.join("app");
+    let mut dirs = BTreeMap::new();
+    let mut claimed = Vec::new();
+    for (name, rel) in projects {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+        if !valid {
+            return Err(format!(
+                "project name {name:?} may only contain letters, digits, '-' and '_'"
+            ));
+        }
+        if name == MAIN_PROJECT {
+            return Err(
+                "project name \"main\" is reserved for the project set by `project`".to_string()
+            );
+        }
+        let dir = project_dir(repo_root, &format!("projects.{name}"), rel)?;
+        dirs.insert(name.clone(), app_dir.join(&dir));
+        claimed.push(dir);
+    }
+
+    if !project.trim().is_empty() {
+        let main = project_dir(repo_root, "project", project)?;
+        if claimed.contains(&main) {
+            return Err(
+                "config field project names a directory also listed in [projects]".to_string()
+            );
+        }
+        dirs.insert(MAIN_PROJECT.to_string(), app_dir.join(main));
+        return Ok(dirs);
+    }
+
+    // Without `project`, the installer picks the only unclaimed app/<dir> with a pyproject.toml.
+    let mut candidates = Vec::new();
+    if app_dir.is_dir() {
+        let entries = fs::read_dir(&app_dir)
+            .map_err(|err| format!("read_dir {}: {err}", app_dir.display()))?;
+        for ent in entries {
+            let ent = ent.map_err(|err| format!("read_dir {}: {err}", app_dir.display()))?;
+            let name = ent.file_name().to_string_lossy().to_string();
+            if ent.path().join("pyproject.toml").exists() && !claimed.contains(&name) {
+                candidates.push(name);
+            }
+        }
+    }
+    candidates.sort();
+    match candidates.len() {
+        0 => Err("no project found: expected app/<dir>/pyproject.toml".to_string()),
+        1 => {
+            dirs.insert(MAIN_PROJECT.to_string(), app_dir.join(&candidates[0]));
+            Ok(dirs)
+        }
+        _ => Err(format!(
+            "several projects found under app/ ({}); set project = \"app/<dir>\" in config.toml",
+            candidates.join(", ")
+        )),
+    }
+}
+
+/// Checks that `rel` is `app/<dir>` with a pyproject.toml and returns `<dir>`.
+fn project_dir(repo_root: &Path, field: &str, rel: &str) -> Result<String, String> {
+    let normalized = rel.trim().replace('\\', "/");
+    let dir = match normalized.trim_end_matches('/').split('/').collect::<Vec<_>>()[..] {
+        ["app", dir] if !dir.is_empty() && dir != "." && dir != ".." => dir.to_string(),
+        _ => return Err(format!("config field {field} must be app/<dir>, got {rel:?}")),
+    };
+    let pyproject = repo_root.join("app").join(&dir).join("pyproject.toml");
+    if !pyproject.exists() {
+        return Err(format!("config field {field}: {} not found", pyproject.display()));
+    }
+    Ok(dir)
+}
+
+/// A named entry point as far as `apply` is concerned.
+pub struct NamedTarget<'a> {
+    /// Name from `[projects]`; empty for the main project.
+    pub project: &'a str,
+    pub target: &'a str,
+    pub console: &'a mut bool,
+}
+
+/// Defaults an unset `entry_point` from the main project's declared scripts and moves
+/// entry points that run a gui-script to the windowed launcher. Returns what each project
+/// declares.
+pub fn apply<'a>(
+    project_dirs: &BTreeMap<String, PathBuf>,
+    entry_point: &mut String,
+    console: &mut bool,
+    named: impl IntoIterator<Item = NamedTarget<'a>>,
+) -> Result<BTreeMap<String, DeclaredScripts>, String> {
+    let mut declared = BTreeMap::new();
+    for (name, dir) in project_dirs {
+        declared.insert(name.clone(), DeclaredScripts::read(dir)?);
+    }
+    let (Some(main), Some(main_dir)) = (declared.get(MAIN_PROJECT), project_dirs.get(MAIN_PROJECT))
+    else {
+        return Ok(declared);
+    };
+    if entry_point.trim().is_empty() {
+        *entry_point = default_entry_point(main, main_dir)?;
+    }
+    if command_name(entry_point).is_some_and(|name| main.is_gui(name)) {
+        *console = false;
+    }
+    for entry in named {
+        let project = match entry.project.trim() {
+            "" => MAIN_PROJECT,
+            project => project,
+        };
+        let gui = declared
+            .get(project)
+            .zip(command_name(entry.target))
+            .is_some_and(|(scripts, name)| scripts.is_gui(name));
+        if gui {
+            *entry.console = false;
+        }
+    }
+    Ok(declared)
+}
+
+/// A lone gui-script, else a lone console script, else main.py.
+fn default_entry_point(declared: &DeclaredScripts, project_dir: &Path) -> Result<String, String> {
+    let candidates = if declared.gui_scripts.is_empty() {
+        &declared.scripts
+    } else {
+        &declared.gui_scripts
+    };
+    if candidates.len() == 1 {
+        return Ok(format!("command:{}", candidates[0]));
+    }
+    if project_dir.join("main.py").exists() {
+        return Ok("main.py".to_string());
+    }
+    if candidates.is_empty() {
+        return Err(format!(
+            "config field entry_point is required: {} declares no scripts and has no main.py",
+            project_dir.display()
+        ));
+    }
+    Err(format!(
+        "config field entry_point is required: {} declares several scripts ({})",
+        project_dir.display(),
+        candidates.join(", ")
+    ))
+}
@@ -1,12 +1,17 @@
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 use std::{
+    collections::BTreeMap,
     env,
     fs,
     path::{Path, PathBuf},
     process::Command,
 };
 
+mod declared_scripts;
+
+use declared_scripts::{command_name, DeclaredScripts, NamedTarget, MAIN_PROJECT};
+
 #[derive(Debug, Deserialize)]
 struct Config {
     app_id: String,
@@ -15,11 +20,75 @@ struct Config {
     company: String,
     description: String,
     version: String,
+    /// Defaults to the main project's declared script, see `apply_declared_scripts`.
+    #[serde(default)]
     entry_point: String,
+    /// Main project directory, `app/<dir>`; required when `app/` holds several projects.
+    #[serde(default)]
+    project: String,
+    /// Extra projects by name, each synced into its own venv.
+    #[serde(default)]
+    projects: BTreeMap<String, String>,
     #[serde(default)]
     icon: String,
     #[serde(default)]
     install_dir: String,
+    #[serde(default)]
+    console: bool,
+    #[serde(default)]
+    entry_points: BTreeMap<String, EntryPointConfig>,
+    #[serde(default)]
+    retry: RetryConfig,
+    #[serde(default)]
+    logs: LogsConfig,
+    /// Extra variables for the app; values may use entry point placeholders.
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+}
+
+/// An additional named entry point from `[entry_points.<name>]`.
+#[derive(Debug, Default, Deserialize)]
+struct EntryPointConfig {
+    target: String,
+    /// Name from `[projects]`; empty for the main project.
+    #[serde(default)]
+    project: String,
+    #[serde(default)]
+    console: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RetryConfig {
+    attempts: usize,
+    python_install_timeout_secs: u64,
+    sync_timeout_secs: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            python_install_timeout_secs: 600,
+            sync_timeout_secs: 1800,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct LogsConfig {
+    max_size_kb: u64,
+    retention: usize,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: 1024,
+            retention: 5,
+        }
+    }
 }
 
 fn main() -> Result<()> {
@@ -34,17 +103,26 @@ fn main() -> Result<()> {
         .map(|p| absolutize_path(&repo_root, p))
         .unwrap_or_else(|| repo_root.join("dist"));
 
-    let config = load_config(&config_path)?;
+    let mut config = load_config(&config_path)?;
+    apply_declared_scripts(&mut config, &repo_root)?;
     validate_config(&config, &repo_root)?;
 
     let installer_dir = repo_root.join("installer-rust");
     let shim_dir = repo_root.join("launcher-rust");
     let installer_ui_dir = repo_root.join("tauri-ui-rust").join("webview-installer-rust");
 
-    build_launcher(&shim_dir)?;
+    build_shim(&shim_dir, false)?;
+    stage_shim_for_installer(&shim_dir, &installer_dir, "launcher.exe")?;
+    let console_shim = installer_dir.join("embedded").join("launcher-console.exe");
+    if needs_console_shim(&config) {
+        build_shim(&shim_dir, true)?;
+        stage_shim_for_installer(&shim_dir, &installer_dir, "launcher-console.exe")?;
+    } else if console_shim.exists() {
+        fs::remove_file(&console_shim)
+            .with_context(|| format!("remove {}", console_shim.display()))?;
+    }
     stage_installer_ui_icon(&installer_ui_dir, &repo_root, &config.icon)?;
     build_installer_ui(&installer_ui_dir)?;
-    stage_shim_for_installer(&shim_dir, &installer_dir)?;
     stage_installer_ui_for_installer(&installer_ui_dir, &installer_dir)?;
     build_launcher(&installer_dir)?;
 
@@ -101,6 +179,133 @@ fn validate_config(config: &Config, repo_root: &Path) -> Result<()> {
         }
     }
 
+    let project_dirs = validate_projects(config, repo_root)?;
+    validate_entry_points(config)?;
+    validate_commands(config, &project_dirs)?;
+    validate_retry(&config.retry)?;
+    validate_logs(&config.logs)?;
+    validate_env(&config.env)?;
+
+    Ok(())
+}
+
+fn validate_entry_points(config: &Config) -> Result<()> {
+    let app_exe = sanitize_exe_name(if config.product_name.trim().is_empty() {
+        &config.name
+    } else {
+        &config.product_name
+    });
+    for (name, entry) in &config.entry_points {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+        if !valid {
+            bail!("entry point name {name:?} may only contain letters, digits, '-' and '_'");
+        }
+        let lowered = name.to_ascii_lowercase();
+        if ["uv", "uvx", "uvw"].contains(&lowered.as_str())
+            || lowered.contains("uninstall")
+            || lowered == app_exe.to_ascii_lowercase()
+        {
+            bail!("entry point name {name:?} clashes with a file the installer writes");
+        }
+        require_field(&format!("entry_points.{name}.target"), &entry.target)?;
+        let project = entry.project.trim();
+        if !project.is_empty() && project != MAIN_PROJECT && !config.projects.contains_key(project) {
+            bail!("entry point {name:?} uses project {project:?}, which is not in [projects]");
+        }
+    }
+    Ok(())
+}
+
+/// Validates the project settings and returns each project's directory by name.
+fn validate_projects(config: &Config, repo_root: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    declared_scripts::project_dirs(&config.project, &config.projects, repo_root)
+        .map_err(anyhow::Error::msg)
+}
+
+/// Defaults an unset `entry_point` from the main project's declared scripts and moves
+/// entry points that run a gui-script to the windowed launcher.
+fn apply_declared_scripts(config: &mut Config, repo_root: &Path) -> Result<()> {
+    let project_dirs = validate_projects(config, repo_root)?;
+    let unset = config.entry_point.trim().is_empty();
+    let named = config.entry_points.values_mut().map(|entry| NamedTarget {
+        project: &entry.project,
+        target: &entry.target,
+        console: &mut entry.console,
+    });
+    let declared =
+        declared_scripts::apply(&project_dirs, &mut config.entry_point, &mut config.console, named)
+            .map_err(anyhow::Error::msg)?;
+    for (name, scripts) in &declared {
+        println!(
+            "project {name}: scripts [{}], gui-scripts [{}]",
+            scripts.scripts.join(", "),
+            scripts.gui_scripts.join(", ")
+        );
+    }
+    if unset {
+        println!("entry_point defaults to {}", config.entry_point);
+    }
+    Ok(())
+}
+
+/// Rejects `command:` entry points that the target project does not declare.
+fn validate_commands(config: &Config, project_dirs: &BTreeMap<String, PathBuf>) -> Result<()> {
+    let named = config.entry_points.iter().map(|(name, entry)| {
+        let project = match entry.project.trim() {
+            "" => MAIN_PROJECT,
+            project => project,
+        };
+        (format!("entry_points.{name}.target"), entry.target.as_str(), project)
+    });
+    let main = ("entry_point".to_string(), config.entry_point.as_str(), MAIN_PROJECT);
+    for (field, target, project) in std::iter::once(main).chain(named) {
+        let (Some(name), Some(dir)) = (command_name(target), project_dirs.get(project)) else {
+            continue;
+        };
+        if !DeclaredScripts::read(dir).map_err(anyhow::Error::msg)?.declares(name) {
+            bail!(
+                "config field {field}: command {name:?} is not declared in [project.scripts] or [project.gui-scripts] of {}",
+                dir.join("pyproject.toml").display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn needs_console_shim(config: &Config) -> bool {
+    config.console || config.entry_points.values().any(|entry| entry.console)
+}
+
+fn validate_logs(logs: &LogsConfig) -> Result<()> {
+    if logs.max_size_kb == 0 {
+        bail!("config field logs.max_size_kb must be greater than 0");
+    }
+    Ok(())
+}
+
+fn validate_env(env: &BTreeMap<String, String>) -> Result<()> {
+    for key in env.keys() {
+        if key.is_empty() || key.contains(['=', '\0']) {
+            bail!("env variable name {key:?} is invalid");
+        }
+        let upper = key.to_ascii_uppercase();
+        if upper.starts_with("UVESSEL_") || upper.starts_with("UV_") {
+            bail!("env variable {key} is reserved for the launcher");
+        }
+    }
+    Ok(())
+}
+
+fn validate_retry(retry: &RetryConfig) -> Result<()> {
+    if retry.attempts == 0 {
+        bail!("config field retry.attempts must be at least 1");
+    }
+    if retry.python_install_timeout_secs == 0 || retry.sync_timeout_secs == 0 {
+        bail!("config retry timeouts must be greater than 0");
+    }
     Ok(())
 }
 
@@ -112,9 +317,22 @@ fn require_field(name: &str, value: &str) -> Result<()> {
 }
 
 fn build_launcher(launcher_dir: &Path) -> Result<()> {
-    let status = Command::new("cargo")
-        .arg("build")
-        .arg("--release")
+    cargo_build_release(launcher_dir, &[])
+}
+
+/// Builds the launcher shim, using the console-subsystem variant for console entry points.
+fn build_shim(shim_dir: &Path, console: bool) -> Result<()> {
+    let features: &[&str] = if console { &["console"] } else { &[] };
+    cargo_build_release(shim_dir, features)
+}
+
+fn cargo_build_release(launcher_dir: &Path, features: &[&str]) -> Result<()> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build").arg("--release");
+    if !features.is_empty() {
+        cmd.arg("--features").arg(features.join(","));
+    }
+    let status = cmd
         .current_dir(launcher_dir)
         .status()
         .with_context(|| format!("build in {}", launcher_dir.display()))?;
@@ -124,7 +342,7 @@ fn build_launcher(launcher_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-fn stage_shim_for_installer(shim_dir: &Path, installer_dir: &Path) -> Result<()> {
+fn stage_shim_for_installer(shim_dir: &Path, installer_dir: &Path, name: &str) -> Result<()> {
     let shim_exe = shim_dir
         .join("target")
         .join("release")
@@ -134,7 +352,7 @@ fn stage_shim_for_installer(shim_dir: &Path, installer_dir: &Path) -> Result<()>
     }
     let embedded_dir = installer_dir.join("embedded");
     fs::create_dir_all(&embedded_dir).context("create embedded dir")?;
-    let dest = embedded_dir.join("launcher.exe");
+    let dest = embedded_dir.join(name);
     fs::copy(&shim_exe, &dest).with_context(|| {
         format!(
             "copy {} -> {}",
@@ -296,6 +514,19 @@ fn find_upwards(start: &Path) -> Option<PathBuf> {
 mod tests {
     use super::*;
 
+    /// A config.toml with the required fields, followed by `extra`.
+    fn parse_config(extra: &str) -> Config {
+        let base = r#"
+            app_id = "com.example.app"
+            name = "app"
+            product_name = "My App"
+            company = "Example"
+            description = "Example app"
+            version = "1.0.0"
+        "#;
+        toml::from_str(&format!("{base}\n{extra}")).unwrap()
+    }
+
     #[test]
     fn sanitize_exe_name_replaces_bad_chars() {
         let name = "My:App*Name?.exe";
@@ -315,6 +546,89 @@ mod tests {
         assert!(err.to_string().contains("config field name is required"));
     }
 
+    #[test]
+    fn validate_entry_points_rejects_clashing_names() {
+
+        let ok = parse_config("[entry_points.cli]\ntarget = \"module:app.cli\"\nconsole = true");
+        assert!(validate_entry_points(&ok).is_ok());
+        assert!(needs_console_shim(&ok));
+
+        let bad_name = parse_config("[entry_points.\"my cli\"]\ntarget = \"cli.py\"");
+        assert!(validate_entry_points(&bad_name).is_err());
+
+        let clash = parse_config("[entry_points.uv]\ntarget = \"cli.py\"");
+        assert!(validate_entry_points(&clash).is_err());
+    }
+
+    #[test]
+    fn validate_projects_requires_explicit_main_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for dir in ["gui", "tool"] {
+            fs::create_dir_all(root.join("app").join(dir)).unwrap();
+            fs::write(root.join("app").join(dir).join("pyproject.toml"), "").unwrap();
+        }
+
+        let ambiguous = parse_config("");
+        let err = validate_projects(&ambiguous, root).unwrap_err();
+        assert!(err.to_string().contains("gui, tool"));
+
+        assert!(validate_projects(&parse_config("project = \"app/gui\""), root).is_ok());
+        let sidecar = parse_config("[projects]\ntool = \"app/tool\"");
+        assert!(validate_projects(&sidecar, root).is_ok());
+
+        assert!(validate_projects(&parse_config("project = \"gui\""), root).is_err());
+        assert!(validate_projects(&parse_config("project = \"app/missing\""), root).is_err());
+        assert!(validate_projects(&parse_config("[projects]\nmain = \"app/tool\""), root).is_err());
+    }
+
+    #[test]
+    fn declared_scripts_pick_default_and_reject_unknown_commands() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let proj = root.join("app").join("game");
+        fs::create_dir_all(&proj).unwrap();
+        fs::write(
+            proj.join("pyproject.toml"),
+            "[project]\nname = \"game\"\n\n[project.scripts]\ngame-cli = \"game.cli:main\"\n\n[project.gui-scripts]\ngame = \"game.app:run\"\n",
+        )
+        .unwrap();
+
+        let mut config = parse_config(
+            "console = true\n[entry_points.cli]\ntarget = \"command:game-cli --verbose\"\nconsole = true",
+        );
+        apply_declared_scripts(&mut config, root).unwrap();
+        assert_eq!(config.entry_point, "command:game");
+        assert!(!config.console);
+        assert!(config.entry_points["cli"].console);
+
+        let dirs = validate_projects(&config, root).unwrap();
+        assert!(validate_commands(&config, &dirs).is_ok());
+        let unknown = parse_config("entry_point = \"command:other\"");
+        let err = validate_commands(&unknown, &dirs).unwrap_err();
+        assert!(err.to_string().contains("\"other\" is not declared"));
+    }
+
+    #[test]
+    fn validate_env_rejects_reserved_names() {
+        let env = |key: &str| BTreeMap::from([(key.to_string(), "{data_dir}".to_string())]);
+        assert!(validate_env(&env("APP_MODE")).is_ok());
+        assert!(validate_env(&env("uvessel_root")).is_err());
+        assert!(validate_env(&env("UV_CACHE_DIR")).is_err());
+        assert!(validate_env(&env("A=B")).is_err());
+    }
+
+    #[test]
+    fn validate_retry_rejects_zero_attempts() {
+        let retry = RetryConfig {
+            attempts: 0,
+            ..RetryConfig::default()
+        };
+        let err = validate_retry(&retry).unwrap_err();
+        assert!(err.to_string().contains("retry.attempts"));
+        assert!(validate_retry(&RetryConfig::default()).is_ok());
+    }
+
     #[test]
     fn parse_arg_finds_value() {
         let args = vec![
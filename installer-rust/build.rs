@@ -1,10 +1,18 @@
 use std::{
+    collections::BTreeMap,
     fs::{self, File},
     io::{self, Read, Write},
     path::{Path, PathBuf},
 };
 use serde::Deserialize;
 
+// `DeclaredScripts::declares` only backs the builder's validation.
+#[allow(dead_code)]
+#[path = "../builder-rust/src/declared_scripts.rs"]
+mod declared_scripts;
+#[path = "src/entry_spec.rs"]
+mod entry_spec;
+
 fn main() {
     let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
     let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
@@ -13,14 +21,24 @@ fn main() {
     let app_dir = repo_root.join("app");
     println!("cargo:rerun-if-changed={}", app_dir.display());
     let shim_path = manifest_dir.join("embedded").join("launcher.exe");
+    let console_shim_path = manifest_dir.join("embedded").join("launcher-console.exe");
     let ui_path = manifest_dir.join("embedded").join("installer-ui.exe");
     println!("cargo:rerun-if-changed={}", shim_path.display());
+    println!("cargo:rerun-if-changed={}", console_shim_path.display());
     println!("cargo:rerun-if-changed={}", ui_path.display());
     let out_path = PathBuf::from(&out_dir).join("app_payload.zip");
     let root_dir = app_dir.parent().unwrap_or(&app_dir);
-    let config = load_config(&repo_root).unwrap_or_else(|err| {
+    let mut config = load_config(&repo_root).unwrap_or_else(|err| {
         panic!("failed to load config.toml: {err}");
     });
+    if let Err(err) = apply_declared_scripts(&mut config, &repo_root) {
+        panic!("failed to read project scripts: {err}");
+    }
+    println!("cargo:rerun-if-changed=src/entry_spec.rs");
+    println!("cargo:rerun-if-changed=../builder-rust/src/declared_scripts.rs");
+    if let Err(err) = validate_templates(&config) {
+        panic!("invalid config.toml: {err}");
+    }
 
     if !shim_path.exists() {
         panic!(
@@ -28,6 +46,16 @@ fn main() {
             shim_path.display()
         );
     }
+    let needs_console = config.console || config.entry_points.values().any(|e| e.console);
+    if needs_console && !console_shim_path.exists() {
+        panic!(
+            "embedded console shim not found at {} (build the shim with --features console)",
+            console_shim_path.display()
+        );
+    }
+    if let Err(err) = stage_console_shim(&console_shim_path, &PathBuf::from(&out_dir)) {
+        panic!("failed to stage console shim: {err}");
+    }
     if !ui_path.exists() {
         panic!(
             "embedded installer ui not found at {} (build the installer ui first)",
@@ -52,6 +80,17 @@ fn main() {
     }
 }
 
+/// Copies the console shim into OUT_DIR, or leaves an empty file when no entry point needs it.
+fn stage_console_shim(src: &Path, out_dir: &Path) -> io::Result<()> {
+    let dest = out_dir.join("launcher-console.exe");
+    if src.exists() {
+        fs::copy(src, &dest)?;
+    } else {
+        fs::write(&dest, [])?;
+    }
+    Ok(())
+}
+
 fn write_payload_zip(app_dir: &Path, root_dir: &Path, out_path: &Path) -> io::Result<()> {
     let file = File::create(out_path)?;
     let mut zip = zip::ZipWriter::new(file);
@@ -112,11 +151,133 @@ struct Config {
     company: String,
     description: String,
     version: String,
+    #[serde(default)]
     entry_point: String,
     #[serde(default)]
+    project: String,
+    #[serde(default)]
+    projects: BTreeMap<String, String>,
+    #[serde(default)]
     icon: String,
     #[serde(default)]
     install_dir: String,
+    #[serde(default = "default_venv_size_mb")]
+    venv_size_mb: u64,
+    #[serde(default)]
+    entry_args: Vec<String>,
+    #[serde(default)]
+    console: bool,
+    #[serde(default)]
+    entry_points: BTreeMap<String, EntryPointConfig>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    env_policy: EnvPolicyConfig,
+    #[serde(default)]
+    retry: RetryConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntryPointConfig {
+    target: String,
+    #[serde(default)]
+    project: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    console: bool,
+    #[serde(default)]
+    shortcut: bool,
+    #[serde(default)]
+    shortcut_name: String,
+}
+
+fn default_venv_size_mb() -> u64 {
+    512
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct EnvPolicyConfig {
+    isolate: bool,
+    allow: Vec<String>,
+}
+
+impl Default for EnvPolicyConfig {
+    fn default() -> Self {
+        Self {
+            isolate: true,
+            allow: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RetryConfig {
+    attempts: usize,
+    initial_delay_ms: u64,
+    max_delay_ms: u64,
+    python_install_timeout_secs: u64,
+    sync_timeout_secs: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_delay_ms: 250,
+            max_delay_ms: 5000,
+            python_install_timeout_secs: 600,
+            sync_timeout_secs: 1800,
+        }
+    }
+}
+
+/// Rejects entry points the installer would fail to parse and placeholders the launcher
+/// could not expand, before anything is embedded.
+fn validate_templates(config: &Config) -> Result<(), String> {
+    let check = |field: &str, raw: &str, args: &[String]| -> Result<(), String> {
+        let target = raw
+            .trim()
+            .strip_prefix("module:")
+            .or_else(|| raw.trim().strip_prefix("command:"))
+            .unwrap_or(raw);
+        entry_spec::split(target).map_err(|err| format!("{field}: {err}"))?;
+        for arg in args {
+            entry_spec::check_placeholders(arg).map_err(|err| format!("{field}: {err}"))?;
+        }
+        Ok(())
+    };
+    if !config.entry_point.trim().is_empty() {
+        check("entry_point", &config.entry_point, &config.entry_args)?;
+    }
+    for (name, entry) in &config.entry_points {
+        check(&format!("entry_points.{name}"), &entry.target, &entry.args)?;
+    }
+    for (key, value) in &config.env {
+        entry_spec::check_placeholders(value).map_err(|err| format!("env.{key}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// The builder's defaults from `declared_scripts`. Without a resolvable project there is
+/// nothing to default from; the builder reports that before it builds the installer.
+fn apply_declared_scripts(config: &mut Config, repo_root: &Path) -> Result<(), String> {
+    let dirs = declared_scripts::project_dirs(&config.project, &config.projects, repo_root);
+    let Ok(project_dirs) = dirs else {
+        return Ok(());
+    };
+    for dir in project_dirs.values() {
+        println!("cargo:rerun-if-changed={}", dir.join("pyproject.toml").display());
+    }
+    let named = config.entry_points.values_mut().map(|entry| declared_scripts::NamedTarget {
+        project: &entry.project,
+        target: &entry.target,
+        console: &mut entry.console,
+    });
+    declared_scripts::apply(&project_dirs, &mut config.entry_point, &mut config.console, named)?;
+    Ok(())
 }
 
 fn load_config(repo_root: &Path) -> io::Result<Config> {
@@ -177,15 +338,67 @@ fn resolve_icon_path(repo_root: &Path, config: &Config) -> Option<PathBuf> {
 fn write_config_rs(out_dir: &Path, config: &Config) -> io::Result<()> {
     let out_path = out_dir.join("uvessel_config.rs");
     let mut file = File::create(&out_path)?;
+    // Only the Windows instance mutex names use the app id.
+    writeln!(file, "#[cfg_attr(not(windows), allow(dead_code))]")?;
+    writeln!(file, "pub const APP_ID: &str = {:?};", config.app_id)?;
     writeln!(file, "pub const NAME: &str = {:?};", config.name)?;
     writeln!(file, "pub const PRODUCT_NAME: &str = {:?};", config.product_name)?;
     writeln!(file, "pub const VERSION: &str = {:?};", config.version)?;
     writeln!(file, "pub const ENTRY_POINT: &str = {:?};", config.entry_point)?;
+    writeln!(file, "pub const PROJECT: &str = {:?};", config.project)?;
+    writeln!(file, "pub const PROJECTS: &[(&str, &str)] = &[")?;
+    for (name, path) in &config.projects {
+        writeln!(file, "    ({:?}, {:?}),", name, path)?;
+    }
+    writeln!(file, "];")?;
+    writeln!(file, "pub const ENTRY_ARGS: &[&str] = &{:?};", config.entry_args)?;
+    writeln!(file, "pub const CONSOLE: bool = {};", config.console)?;
+    writeln!(file, "pub struct NamedEntryPoint {{")?;
+    writeln!(file, "    pub name: &'static str,")?;
+    writeln!(file, "    pub target: &'static str,")?;
+    writeln!(file, "    pub project: &'static str,")?;
+    writeln!(file, "    pub args: &'static [&'static str],")?;
+    writeln!(file, "    pub console: bool,")?;
+    writeln!(file, "    pub shortcut: bool,")?;
+    writeln!(file, "    pub shortcut_name: &'static str,")?;
+    writeln!(file, "}}")?;
+    writeln!(file, "pub const ENTRY_POINTS: &[NamedEntryPoint] = &[")?;
+    for (name, entry) in &config.entry_points {
+        writeln!(
+            file,
+            "    NamedEntryPoint {{ name: {:?}, target: {:?}, project: {:?}, args: &{:?}, console: {}, shortcut: {}, shortcut_name: {:?} }},",
+            name,
+            entry.target,
+            entry.project,
+            entry.args,
+            entry.console,
+            entry.shortcut,
+            entry.shortcut_name
+        )?;
+    }
+    writeln!(file, "];")?;
     writeln!(file, "pub const ICON: &str = {:?};", config.icon)?;
     writeln!(
         file,
         "pub const INSTALL_DIR: &str = {:?};",
         config.install_dir
     )?;
+    writeln!(
+        file,
+        "pub const VENV_SIZE_ESTIMATE_BYTES: u64 = {};",
+        config.venv_size_mb * 1024 * 1024
+    )?;
+    writeln!(file, "pub const ENV_ISOLATE: bool = {};", config.env_policy.isolate)?;
+    writeln!(file, "pub const ENV_ALLOW: &[&str] = &{:?};", config.env_policy.allow)?;
+    let retry = &config.retry;
+    writeln!(file, "pub const RETRY_ATTEMPTS: usize = {};", retry.attempts)?;
+    writeln!(file, "pub const RETRY_INITIAL_DELAY_MS: u64 = {};", retry.initial_delay_ms)?;
+    writeln!(file, "pub const RETRY_MAX_DELAY_MS: u64 = {};", retry.max_delay_ms)?;
+    writeln!(
+        file,
+        "pub const PYTHON_INSTALL_TIMEOUT_SECS: u64 = {};",
+        retry.python_install_timeout_secs
+    )?;
+    writeln!(file, "pub const SYNC_TIMEOUT_SECS: u64 = {};", retry.sync_timeout_secs)?;
     Ok(())
 }
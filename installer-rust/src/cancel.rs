@@ -0,0 +1,89 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Error returned when the user cancels the install; callers check for it with `is_cancelled`.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "install cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+pub fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Cancelled>().is_some()
+}
+
+/// Cancellation flag shared between the install steps, set either directly or by the UI
+/// writing the cancel marker file.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+    marker: Option<PathBuf>,
+}
+
+impl CancelToken {
+    pub fn new(marker: Option<PathBuf>) -> Self {
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+            marker,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        if self.flag.load(Ordering::SeqCst) {
+            return true;
+        }
+        let requested = self.marker.as_ref().map(|p| p.exists()).unwrap_or(false);
+        if requested {
+            self.flag.store(true, Ordering::SeqCst);
+        }
+        requested
+    }
+
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_file_cancels_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let marker = tmp.path().join("cancel.flag");
+        let token = CancelToken::new(Some(marker.clone()));
+        assert!(token.check().is_ok());
+
+        std::fs::write(&marker, "cancel").unwrap();
+        let err = token.check().unwrap_err();
+        assert!(is_cancelled(&err));
+    }
+
+    #[test]
+    fn clones_share_cancellation() {
+        let token = CancelToken::new(None);
+        let other = token.clone();
+        other.cancel();
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,115 @@
+//! Entry point syntax shared with build.rs and the launcher, so it only depends on std.
+
+/// Placeholders the launcher expands in entry point arguments at run time.
+pub const PLACEHOLDERS: &[&str] = &["install_root", "data_dir", "assets_dir", "version"];
+
+/// Splits an entry point into its target and arguments. Arguments are separated by
+/// whitespace; double quotes group an argument that contains spaces.
+pub fn split(raw: &str) -> Result<(String, Vec<String>), String> {
+    let mut words = split_words(raw)?.into_iter();
+    let target = words.next().ok_or_else(|| "entry point is empty".to_string())?;
+    if target.contains('{') {
+        return Err(format!(
+            "{target:?}: placeholders are only expanded in arguments"
+        ));
+    }
+    let args: Vec<String> = words.collect();
+    for arg in &args {
+        check_placeholders(arg)?;
+    }
+    Ok((target, args))
+}
+
+/// Checks that every `{name}` in `arg` is a known placeholder; `{{` and `}}` are literal braces.
+pub fn check_placeholders(arg: &str) -> Result<(), String> {
+    expand(arg, |name| PLACEHOLDERS.contains(&name).then(String::new)).map(|_| ())
+}
+
+/// Replaces each `{name}` in `arg` with `lookup(name)`, failing on names it does not know;
+/// `{{` and `}}` stand for literal braces.
+pub fn expand(arg: &str, mut lookup: impl FnMut(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(arg.len());
+    let mut chars = arg.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("{arg:?}: unclosed '{{'")),
+                    }
+                }
+                match lookup(&name) {
+                    Some(value) => out.push_str(&value),
+                    None => {
+                        return Err(format!(
+                            "{arg:?}: unknown placeholder {{{name}}} (expected one of {})",
+                            PLACEHOLDERS.join(", ")
+                        ))
+                    }
+                }
+            }
+            '}' => return Err(format!("{arg:?}: unmatched '}}' (use '}}}}' for a literal brace)")),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn split_words(raw: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    for ch in raw.chars() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => {
+                words.extend(current.take());
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("{raw:?}: unterminated quote"));
+    }
+    words.extend(current);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_keeps_quoted_arguments_together() {
+        let (target, args) =
+            split(r#"myapp --config "{data_dir}\my app.toml" --name """#).unwrap();
+        assert_eq!(target, "myapp");
+        assert_eq!(args, [r"--config", r"{data_dir}\my app.toml", "--name", ""]);
+        assert!(split("myapp \"open").is_err());
+        assert!(split("   ").is_err());
+    }
+
+    #[test]
+    fn check_placeholders_rejects_unknown_names() {
+        assert!(check_placeholders("{install_root}/data/{version}.toml").is_ok());
+        assert!(check_placeholders("{{literal}}").is_ok());
+        assert!(check_placeholders("{home}").is_err());
+        assert!(check_placeholders("{install_root").is_err());
+        assert!(check_placeholders("a}b").is_err());
+        assert!(split("{install_root}/bin/tool").is_err());
+    }
+}
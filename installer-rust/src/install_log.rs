@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use crate::timestamp;
+
+/// Number of installer logs kept per directory; older ones are removed when a new log starts.
+const KEEP_LOGS: usize = 10;
+const LOG_PREFIX: &str = "installer-";
+const LOG_EXT: &str = "log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    ts: String,
+    level: Level,
+    phase: &'a str,
+    message: &'a str,
+}
+
+/// JSON-lines install log, one record per line with timestamp, level, phase and message.
+#[derive(Debug, Clone, Default)]
+pub struct InstallLog {
+    path: Arc<Mutex<Option<PathBuf>>>,
+    /// The file the log started in, which the installer UI tails; it keeps receiving records
+    /// after a `move_into` until `finish`.
+    ui_path: Arc<Mutex<Option<PathBuf>>>,
+    phase: Arc<Mutex<String>>,
+}
+
+impl InstallLog {
+    /// Starts a new log under the temp dir; `move_into` takes it to the install root once
+    /// that exists, so a failed preflight leaves nothing behind there.
+    pub fn open(app_name: &str) -> Self {
+        Self::open_in(&std::env::temp_dir().join("uvessel-logs").join(app_name))
+    }
+
+    fn open_in(dir: &Path) -> Self {
+        let path = start_log(dir).ok();
+        Self {
+            path: Arc::new(Mutex::new(path.clone())),
+            ui_path: Arc::new(Mutex::new(path)),
+            phase: Arc::new(Mutex::new("startup".to_string())),
+        }
+    }
+
+    /// The current log file.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.lock().ok()?.clone()
+    }
+
+    /// The file to hand to the installer UI; unlike `path`, it does not change on a move.
+    pub fn ui_path(&self) -> Option<PathBuf> {
+        self.ui_path.lock().ok()?.clone()
+    }
+
+    /// Copies the log into `<install_root>/.runtime/logs` and writes there from now on.
+    pub fn move_into(&self, install_root: &Path) -> Result<()> {
+        let Some(current) = self.path() else {
+            return Ok(());
+        };
+        let dir = logs_dir(install_root);
+        fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        rotate_logs(&dir, KEEP_LOGS.saturating_sub(1))?;
+        let dest = dir.join(current.file_name().unwrap_or_default());
+        if dest == current {
+            return Ok(());
+        }
+        fs::copy(&current, &dest)
+            .with_context(|| format!("copy {} -> {}", current.display(), dest.display()))?;
+        if let Ok(mut path) = self.path.lock() {
+            *path = Some(dest);
+        }
+        Ok(())
+    }
+
+    /// Called once the UI is closed: deletes the temp copy of a log that was moved.
+    pub fn finish(&self) {
+        let path = self.path();
+        if let Ok(mut ui_path) = self.ui_path.lock() {
+            if let Some(temp) = ui_path.take() {
+                if path.as_ref() != Some(&temp) {
+                    let _ = fs::remove_file(temp);
+                }
+            }
+        }
+    }
+
+    pub fn set_phase(&self, phase: &str) {
+        if let Ok(mut current) = self.phase.lock() {
+            *current = phase.to_string();
+        }
+    }
+
+    pub fn info(&self, message: &str) -> Result<()> {
+        self.write(Level::Info, message)
+    }
+
+    pub fn warn(&self, message: &str) -> Result<()> {
+        self.write(Level::Warn, message)
+    }
+
+    pub fn error(&self, message: &str) -> Result<()> {
+        self.write(Level::Error, message)
+    }
+
+    fn write(&self, level: Level, message: &str) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        let phase = self.phase.lock().map(|p| p.clone()).unwrap_or_default();
+        let record = Record {
+            ts: timestamp::now(),
+            level,
+            phase: &phase,
+            message,
+        };
+        let line = serde_json::to_string(&record).context("serialize log record")?;
+        append_line(&path, &line)?;
+        if let Some(ui_path) = self.ui_path().filter(|ui_path| *ui_path != path) {
+            append_line(&ui_path, &line)?;
+        }
+        Ok(())
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+pub fn logs_dir(install_root: &Path) -> PathBuf {
+    install_root.join(".runtime").join("logs")
+}
+
+fn start_log(dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    rotate_logs(dir, KEEP_LOGS.saturating_sub(1))?;
+    let stamp = timestamp::format(SystemTime::now(), true);
+    let path = dir.join(format!("{LOG_PREFIX}{stamp}.{LOG_EXT}"));
+    fs::write(&path, "").with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Removes all but the `keep` newest installer logs in `dir`.
+pub fn rotate_logs(dir: &Path, keep: usize) -> Result<()> {
+    let mut logs = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_log = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with(LOG_PREFIX) && n.ends_with(&format!(".{LOG_EXT}")))
+            .unwrap_or(false);
+        if is_log {
+            logs.push(path);
+        }
+    }
+    // Timestamps in the names sort chronologically.
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for path in logs.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_json_lines_with_phase() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = InstallLog::open_in(tmp.path());
+        let path = log.path().unwrap();
+        assert!(path.starts_with(tmp.path()));
+
+        log.set_phase("sync");
+        log.info("Installing dependencies").unwrap();
+        log.error("uv sync failed").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let records: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["phase"], "sync");
+        assert_eq!(records[0]["level"], "info");
+        assert_eq!(records[1]["level"], "error");
+        assert_eq!(records[1]["message"], "uv sync failed");
+    }
+
+    #[test]
+    fn moved_log_keeps_its_records_and_feeds_the_ui_copy_until_finished() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = InstallLog::open_in(&tmp.path().join("temp"));
+        log.info("before preflight").unwrap();
+        let root = tmp.path().join("MyApp");
+        log.move_into(&root).unwrap();
+        log.info("after preflight").unwrap();
+
+        let moved = log.path().unwrap();
+        let ui_copy = log.ui_path().unwrap();
+        assert!(moved.starts_with(logs_dir(&root)));
+        assert_eq!(fs::read_to_string(&moved).unwrap().lines().count(), 2);
+        assert_eq!(fs::read_to_string(&ui_copy).unwrap().lines().count(), 2);
+
+        log.finish();
+        assert!(!ui_copy.exists());
+        assert!(moved.exists());
+    }
+
+    #[test]
+    fn rotate_logs_keeps_newest() {
+        let tmp = tempfile::tempdir().unwrap();
+        for stamp in ["20250101T000000000Z", "20250102T000000000Z", "20250103T000000000Z"] {
+            fs::write(tmp.path().join(format!("installer-{stamp}.log")), "").unwrap();
+        }
+        fs::write(tmp.path().join("launcher.log"), "").unwrap();
+
+        rotate_logs(tmp.path(), 2).unwrap();
+
+        assert!(!tmp.path().join("installer-20250101T000000000Z.log").exists());
+        assert!(tmp.path().join("installer-20250102T000000000Z.log").exists());
+        assert!(tmp.path().join("installer-20250103T000000000Z.log").exists());
+        assert!(tmp.path().join("launcher.log").exists());
+    }
+}
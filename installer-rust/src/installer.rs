@@ -3,27 +3,41 @@ use semver::Version;
 use std::{
     fs,
     path::{Path, PathBuf},
-    process::{Command, ExitStatus, Stdio},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
 };
 
-use crate::{fs_ops, payload, shortcuts, shim_payload, state, ui_payload, uv};
+use crate::cancel::{self, CancelToken};
+use crate::env_lock::EnvLock;
+use crate::install_log::{self, InstallLog};
+use crate::progress::ProgressReporter;
+use crate::retry::{CommandOutput, RetryPolicy};
+use crate::{
+    declared_scripts, diagnostics, env_policy, fs_ops, payload, preflight, processes, shortcuts,
+    shim_payload, state, ui_payload, uv,
+};
+
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(15);
+const FORCE_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+const CLOSE_DECISION_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long the installer or uninstaller waits for a launcher to finish syncing a venv.
+const ENV_LOCK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Copy of the installer UI in `.runtime` that the launcher opens in "error" mode after the
+/// app crashes; the launcher looks for it under this name.
+const ERROR_UI_EXE: &str = "uvessel-ui.exe";
 
-pub fn run(root: &Path) -> Result<()> {
+pub fn run(_root: &Path) -> Result<()> {
     let app_name = app_name_from_config();
     let install_root = crate::paths::default_install_root(&app_name)?;
 
-    let done_marker = create_done_marker_path();
-    let log_path = create_log_path();
-    let _ = init_log_file(&log_path);
-    let launch_marker = create_launch_marker_path();
+    let files = UiFiles::create();
+    let cancel = CancelToken::new(Some(files.cancel.clone()));
+    let progress = ProgressReporter::new(Some(files.progress.clone()));
     let update_mode = detect_update_mode(&install_root)?;
-    let mut ui_child = match launch_installer_ui(
-        &app_name,
-        Some(&done_marker),
-        Some(&log_path),
-        Some(&launch_marker),
-        update_mode,
-    ) {
+    let log = InstallLog::open(&app_name);
+    let ui_log = log.ui_path();
+    let ui_launch = launch_installer_ui(&app_name, &files, ui_log.as_deref(), update_mode);
+    let mut ui_child = match ui_launch {
         Ok(child) => child,
         Err(err) => {
             eprintln!("warning: failed to launch installer ui: {err}");
@@ -31,13 +45,13 @@ pub fn run(root: &Path) -> Result<()> {
         }
     };
 
+    let ui_launched = ui_child.is_some();
     let mut pending_launch: Option<PathBuf> = None;
     let result = run_with_deps(
-        root,
         &install_root,
         &app_name,
         uv::ensure_uv,
-        |cmd| exec_with_log(cmd, Some(&log_path)),
+        |cmd, timeout| exec_with_log(cmd, timeout, &log, &cancel, &progress),
         |start_menu, name, target, icon| {
             shortcuts::create_start_menu_shortcut(start_menu, name, target, icon)
         },
@@ -45,22 +59,40 @@ pub fn run(root: &Path) -> Result<()> {
             pending_launch = Some(exe.to_path_buf());
             Ok(())
         },
-        Some(&log_path),
+        |install_root| {
+            ensure_app_closed(
+                install_root,
+                &app_name,
+                Some(&files.close),
+                ui_launched.then_some(files.close_decision.as_path()),
+                &log,
+            )
+        },
+        &InstallContext {
+            log: &log,
+            preflight_path: Some(&files.preflight),
+            cancel: &cancel,
+            progress: &progress,
+        },
     );
 
-    if result.is_ok() {
-        let _ = std::fs::write(&done_marker, "ok");
-    } else {
-        let _ = std::fs::write(&done_marker, "fail");
-    }
+    let status = match &result {
+        Ok(()) => "ok",
+        Err(err) if cancel::is_cancelled(err) => "cancelled",
+        Err(_) => "fail",
+    };
+    let _ = std::fs::write(&files.done, status);
 
     if let Some(child) = ui_child.as_mut() {
+        if result.is_err() {
+            serve_diagnose_requests(child, &install_root, &files, &log);
+        }
         let _ = child.wait();
     }
 
-    let should_launch = std::fs::metadata(&launch_marker).is_ok();
-    let _ = std::fs::remove_file(&log_path);
-    let _ = std::fs::remove_file(&launch_marker);
+    let should_launch = std::fs::metadata(&files.launch).is_ok();
+    files.cleanup();
+    log.finish();
 
     if result.is_ok() && should_launch {
         if let Some(exe) = pending_launch {
@@ -93,20 +125,34 @@ pub fn run_from_args(root: &Path) -> Result<()> {
     run(root)
 }
 
+/// Where an install reports to, and what can stop it; the installer UI watches these.
+#[derive(Clone, Copy)]
+pub struct InstallContext<'a> {
+    pub log: &'a InstallLog,
+    /// Receives the preflight report.
+    pub preflight_path: Option<&'a Path>,
+    pub cancel: &'a CancelToken,
+    pub progress: &'a ProgressReporter,
+}
+
 pub fn run_with_deps(
-    _root: &Path,
     install_root: &Path,
     app_name: &str,
     ensure_uv_fn: impl Fn(&Path) -> Result<()>,
-    mut exec: impl FnMut(&mut Command) -> Result<ExitStatus>,
+    mut exec: impl FnMut(&mut Command, Duration) -> Result<CommandOutput>,
     create_shortcut_fn: impl Fn(&Path, &str, &Path, Option<&Path>) -> Result<PathBuf>,
     mut launch_fn: impl FnMut(&Path) -> Result<()>,
-    log_path: Option<&Path>,
+    mut close_app_fn: impl FnMut(&Path) -> Result<()>,
+    ctx: &InstallContext,
 ) -> Result<()> {
+    let InstallContext {
+        log,
+        preflight_path,
+        cancel,
+        progress,
+    } = *ctx;
     let install_root_existed = install_root.exists();
-    log_line(log_path, &format!("Starting install for {app_name}"))?;
-    fs::create_dir_all(install_root)
-        .with_context(|| format!("create {}", install_root.display()))?;
+    log.info(&format!("Starting install for {app_name}"))?;
 
     let state_path = state::state_path(install_root);
     let existing_state = if state_path.exists() {
@@ -122,7 +168,8 @@ pub fn run_with_deps(
     let dest_exe = install_root.join(format!("{app_name}.exe"));
     match version_relation {
         VersionRelation::Same => {
-            log_line(log_path, "Installed version matches, launching app")?;
+            log.info("Installed version matches, launching app")?;
+            keep_log_in(log, install_root);
             let icon = resolve_icon_path(install_root);
             if let Ok(start_menu) = shortcuts::default_start_menu_dir() {
                 let uninstall_name = format!("Uninstall {app_name}");
@@ -151,18 +198,68 @@ pub fn run_with_deps(
         VersionRelation::Newer | VersionRelation::Unknown => {}
     }
 
+    log.set_phase("preflight");
+    progress.phase("Checking system", 0, 5);
+    log.info("Running preflight checks")?;
+    let report = preflight::run_checks(install_root, app_name);
+    for check in &report.checks {
+        let line = format!("preflight {}: {}", check.name, check.message);
+        match check.status {
+            preflight::CheckStatus::Pass => log.info(&line)?,
+            preflight::CheckStatus::Warn => log.warn(&line)?,
+            preflight::CheckStatus::Fail => log.error(&line)?,
+        }
+    }
+    if let Some(path) = preflight_path {
+        preflight::write_report(path, &report)?;
+    }
+    if !report.passed() {
+        bail!("preflight checks failed: {}", report.summary());
+    }
+
+    if install_root_existed {
+        close_app_fn(install_root)?;
+    }
+    cancel.check()?;
+
+    fs::create_dir_all(install_root)
+        .with_context(|| format!("create {}", install_root.display()))?;
+    keep_log_in(log, install_root);
+    // Held until the install or its rollback is done, so a launch started meanwhile waits.
+    let _env_lock = EnvLock::acquire(
+        &install_root.join(".runtime"),
+        "installer",
+        ENV_LOCK_TIMEOUT,
+        |msg| {
+            let _ = log.info(msg);
+        },
+    )?;
+
+    let shims = shim_targets(install_root, app_name);
     let mut backup = if existing_state.is_some() {
-        log_line(log_path, "Existing install detected, preparing backup")?;
-        Some(UpgradeBackup::create(install_root)?)
+        log.info("Existing install detected, preparing backup")?;
+        let shim_paths: Vec<PathBuf> = shims.iter().map(|shim| shim.path.clone()).collect();
+        Some(UpgradeBackup::create(install_root, &shim_paths)?)
     } else {
         None
     };
 
-    let install_result = (|| -> Result<()> {
-        log_line(log_path, "Writing launcher shim")?;
-        write_shim_exe(&dest_exe)?;
+    let install_result = (|| -> Result<Vec<String>> {
+        cancel.check()?;
+        log.set_phase("shim");
+        progress.phase("Writing launcher", 5, 8);
+        log.info("Writing launcher shims")?;
+        for shim in &shims {
+            write_shim_exe(shim)?;
+        }
+        if let Err(err) = write_error_ui_exe(install_root) {
+            // Only the crash window is lost, e.g. while one from an earlier crash is open.
+            log.warn(&format!("Could not install the error window: {err:#}"))?;
+        }
 
-        log_line(log_path, "Extracting payload")?;
+        log.set_phase("payload");
+        progress.phase("Extracting files", 8, 15);
+        log.info("Extracting payload")?;
         payload::install_payload_with_options(
             install_root,
             payload::PayloadOptions {
@@ -172,58 +269,91 @@ pub fn run_with_deps(
 
         let icon = resolve_icon_path(install_root);
 
-        log_line(log_path, "Ensuring uv")?;
+        cancel.check()?;
+        log.set_phase("uv");
+        progress.phase("Fetching uv", 15, 20);
+        log.info("Ensuring uv")?;
         ensure_uv_fn(install_root)?;
+        cancel.check()?;
 
         let runtime = install_root.join(".runtime");
         ensure_runtime_dirs(&runtime)?;
 
-        let proj = find_project(install_root)?;
-        let lock_path = proj.join("uv.lock");
-        let lock_mtime = if lock_path.exists() {
-            state::file_mtime_unix(&lock_path)?
-        } else {
-            0
-        };
+        let projects = resolve_projects(install_root, crate::config::PROJECT, crate::config::PROJECTS)?;
 
         let uv_exe = install_root.join("uv.exe");
         if !uv_exe.exists() {
             bail!("uv.exe not found after install at {}", uv_exe.display());
         }
 
-        run_with_retry(
-            || {
-                let mut install = build_uv_cmd(&uv_exe, &proj, &runtime);
-                install.arg("python").arg("install");
-                if let Some(version) = read_python_version(&proj)? {
-                    install.arg(version);
-                }
-                exec(&mut install)
-            },
-            5,
-            "uv python install",
-        )?;
+        let policy = RetryPolicy::from_config();
+        log.set_phase("python");
+        progress.phase("Installing Python", 20, 40);
+        for project in &projects {
+            let venv = state::venv_dir(&runtime, &project.name);
+            run_with_retry(
+                || {
+                    let mut install = build_uv_cmd(&uv_exe, &project.dir, &runtime, &venv);
+                    install.arg("python").arg("install");
+                    if let Some(version) = read_python_version(&project.dir)? {
+                        install.arg(version);
+                    }
+                    exec(&mut install, policy.python_install_timeout)
+                },
+                &policy,
+                "uv python install",
+                log,
+            )?;
+            cancel.check()?;
+        }
 
-        run_with_retry(
-            || {
-                let mut sync = build_uv_cmd(&uv_exe, &proj, &runtime);
-                sync.arg("sync");
-                if lock_path.exists() {
-                    sync.arg("--frozen");
-                }
-                exec(&mut sync)
-            },
-            5,
-            "uv sync",
-        )?;
+        log.set_phase("sync");
+        let mut fingerprints = Vec::with_capacity(projects.len());
+        for (i, project) in projects.iter().enumerate() {
+            let (start, end) = split_range(40, 92, i, projects.len());
+            progress.phase("Installing dependencies", start, end);
+            if projects.len() > 1 {
+                log.info(&format!("Syncing project {}", project.name))?;
+            }
+            let venv = state::venv_dir(&runtime, &project.name);
+            let lock_path = project.dir.join("uv.lock");
+            run_with_retry(
+                || {
+                    let mut sync = build_uv_cmd(&uv_exe, &project.dir, &runtime, &venv);
+                    sync.arg("sync");
+                    if lock_path.exists() {
+                        sync.arg("--frozen");
+                    }
+                    exec(&mut sync, policy.sync_timeout)
+                },
+                &policy,
+                "uv sync",
+                log,
+            )?;
+            fingerprints.push(state::Fingerprint::read(&project.dir, &venv)?);
+            cancel.check()?;
+        }
 
-        log_line(log_path, "Cleaning runtime cache")?;
+        log.set_phase("finalize");
+        progress.phase("Finishing up", 92, 100);
+        log.info("Cleaning runtime cache")?;
         cleanup_uv_cache(&runtime)?;
 
+        // Last point the install can be cancelled; shortcuts and state commit it.
+        cancel.check()?;
         let start_menu = shortcuts::default_start_menu_dir()?;
         create_shortcut_fn(&start_menu, app_name, &dest_exe, icon.as_deref())?;
+        let mut shortcut_names = vec![app_name.to_string()];
+        for (shim, named) in shims.iter().filter_map(|s| s.entry.map(|e| (s, e))) {
+            if named.shortcut {
+                let name = entry_shortcut_name(app_name, named);
+                create_shortcut_fn(&start_menu, &name, &shim.path, icon.as_deref())?;
+                shortcut_names.push(name);
+            }
+        }
         let uninstall_exe = ensure_uninstaller(install_root)?;
         let uninstall_name = format!("Uninstall {app_name}");
+        shortcut_names.push(uninstall_name.clone());
         shortcuts::create_start_menu_shortcut(
             &start_menu,
             &uninstall_name,
@@ -231,35 +361,79 @@ pub fn run_with_deps(
             icon.as_deref(),
         )?;
 
-        let mut st = state::default_state_for_project(install_root, &proj)?;
-        st.lock_mtime_unix = lock_mtime;
+        let manifest = diagnostics::manifest_path(install_root);
+        payload::write_manifest(&manifest, diagnostics::sha256_hex)?;
+        let mut st = state::default_state_for_projects(install_root, &projects)?;
+        for (project, fingerprint) in st.projects.iter_mut().zip(fingerprints) {
+            project.fingerprint = fingerprint;
+        }
+        st.shortcuts = shortcut_names;
         state::write_state(&state::state_path(install_root), &st)?;
 
-        log_line(log_path, "Launching application")?;
+        log.info("Launching application")?;
         launch_fn(&dest_exe)?;
-        Ok(())
+        Ok(st.shortcuts)
     })();
 
     match install_result {
-        Ok(()) => {
-            log_line(log_path, "Install completed successfully")?;
+        Ok(shortcut_names) => {
+            if let Some(previous) = existing_state.as_ref() {
+                let start_menu = shortcuts::default_start_menu_dir().ok();
+                let start_menu = start_menu.as_deref();
+                remove_stale_entries(install_root, start_menu, previous, &shims, &shortcut_names);
+            }
+            progress.finish();
+            log.info("Install completed successfully")?;
             if let Some(mut backup) = backup.take() {
                 backup.cleanup()?;
             }
             Ok(())
         }
         Err(err) => {
-            let _ = log_line(log_path, &format!("Install failed: {err}"));
+            log.set_phase("rollback");
+            if cancel::is_cancelled(&err) {
+                let _ = log.warn("Install cancelled, rolling back");
+            } else {
+                let _ = log.error(&format!("Install failed: {err:#}"));
+            }
             if let Some(mut backup) = backup.take() {
                 let _ = backup.restore();
             } else if !install_root_existed {
-                let _ = fs::remove_dir_all(install_root);
+                let _ = remove_install_keeping_logs(install_root);
             }
             Err(err)
         }
     }
 }
 
+/// Moves the install log from the temp dir into the install root; it stays in the temp dir
+/// when that fails.
+fn keep_log_in(log: &InstallLog, install_root: &Path) {
+    if let Err(err) = log.move_into(install_root) {
+        let _ = log.warn(&format!("Could not move the install log: {err:#}"));
+    }
+}
+
+/// Rolls back a fresh install while keeping the installer logs for troubleshooting.
+fn remove_install_keeping_logs(install_root: &Path) -> Result<()> {
+    let logs = install_log::logs_dir(install_root);
+    if !logs.exists() {
+        return fs::remove_dir_all(install_root)
+            .with_context(|| format!("remove {}", install_root.display()));
+    }
+    let runtime = logs.parent().unwrap_or(install_root);
+    for dir in [install_root, runtime] {
+        for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+            let path = entry?.path();
+            if path == runtime || path == logs {
+                continue;
+            }
+            remove_path(&path)?;
+        }
+    }
+    Ok(())
+}
+
 fn app_name_from_config() -> String {
     let product = crate::config::PRODUCT_NAME.trim();
     if !product.is_empty() {
@@ -284,9 +458,8 @@ fn detect_update_mode(install_root: &Path) -> Result<bool> {
 
 fn launch_installer_ui(
     app_name: &str,
-    done_marker: Option<&Path>,
+    files: &UiFiles,
     log_path: Option<&Path>,
-    launch_marker: Option<&Path>,
     update_mode: bool,
 ) -> Result<Option<std::process::Child>> {
     if ui_payload::EMBEDDED_INSTALLER_UI.is_empty() {
@@ -302,15 +475,18 @@ fn launch_installer_ui(
     if let Some(icon_path) = icon_path {
         cmd.arg("--icon").arg(icon_path);
     }
-    if let Some(marker) = done_marker {
-        cmd.arg("--done-file").arg(marker);
-    }
+    cmd.arg("--done-file").arg(&files.done);
     if let Some(log_path) = log_path {
         cmd.arg("--log-file").arg(log_path);
     }
-    if let Some(launch_marker) = launch_marker {
-        cmd.arg("--launch-file").arg(launch_marker);
-    }
+    cmd.arg("--launch-file").arg(&files.launch);
+    cmd.arg("--preflight-file").arg(&files.preflight);
+    cmd.arg("--close-file").arg(&files.close);
+    cmd.arg("--close-decision-file").arg(&files.close_decision);
+    cmd.arg("--cancel-file").arg(&files.cancel);
+    cmd.arg("--progress-file").arg(&files.progress);
+    cmd.arg("--diagnose-file").arg(&files.diagnose);
+    cmd.arg("--diagnose-result-file").arg(&files.diagnose_result);
     if update_mode {
         cmd.arg("--mode").arg("update");
     }
@@ -366,12 +542,29 @@ fn finalize_uninstall(install_root: &Path, app_name: Option<&str>) -> Result<()>
     if let Some(name) = app_name {
         if let Ok(start_menu) = shortcuts::default_start_menu_dir() {
             let _ = shortcuts::remove_start_menu_shortcut(&start_menu, name);
+            for named in crate::config::ENTRY_POINTS.iter().filter(|e| e.shortcut) {
+                let entry_name = entry_shortcut_name(name, named);
+                let _ = shortcuts::remove_start_menu_shortcut(&start_menu, &entry_name);
+            }
             let uninstall_name = format!("Uninstall {name}");
             let _ = shortcuts::remove_start_menu_shortcut(&start_menu, &uninstall_name);
+            // Shortcuts of entry points that an earlier config had, if any survived.
+            if let Ok(st) = state::read_state(&state::state_path(install_root)) {
+                for recorded in &st.shortcuts {
+                    let _ = shortcuts::remove_start_menu_shortcut(&start_menu, recorded);
+                }
+            }
         }
     }
 
     if install_root.exists() {
+        // Removing the root deletes the lock file too.
+        let _env_lock = EnvLock::acquire(
+            &install_root.join(".runtime"),
+            "uninstaller",
+            ENV_LOCK_TIMEOUT,
+            |_| {},
+        )?;
         fs::remove_dir_all(install_root)
             .with_context(|| format!("remove {}", install_root.display()))?;
     }
@@ -406,6 +599,36 @@ fn write_installer_ui_exe() -> Result<PathBuf> {
     Ok(path)
 }
 
+fn write_error_ui_exe(install_root: &Path) -> Result<()> {
+    if ui_payload::EMBEDDED_INSTALLER_UI.is_empty() {
+        return Ok(());
+    }
+    let runtime = install_root.join(".runtime");
+    fs::create_dir_all(&runtime).with_context(|| format!("create {}", runtime.display()))?;
+    let dest = runtime.join(ERROR_UI_EXE);
+    fs_ops::write_bytes_with_retry(&dest, ui_payload::EMBEDDED_INSTALLER_UI, 3)
+}
+
+/// Writes a support bundle each time the UI asks for one, until the UI exits.
+fn serve_diagnose_requests(
+    ui: &mut std::process::Child,
+    install_root: &Path,
+    files: &UiFiles,
+    log: &InstallLog,
+) {
+    while let Ok(None) = ui.try_wait() {
+        if fs::remove_file(&files.diagnose).is_ok() {
+            let extra_logs: Vec<PathBuf> = log.path().into_iter().collect();
+            let result = match diagnostics::write_bundle(install_root, "installer", &extra_logs) {
+                Ok(path) => serde_json::json!({ "path": path }),
+                Err(err) => serde_json::json!({ "error": format!("{err:#}") }),
+            };
+            let _ = fs::write(&files.diagnose_result, result.to_string());
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
 fn resolve_ui_icon_path() -> Result<Option<PathBuf>> {
     let icon = crate::config::ICON.trim();
     if icon.is_empty() {
@@ -418,80 +641,135 @@ fn resolve_ui_icon_path() -> Result<Option<PathBuf>> {
     payload::extract_embedded_file(icon_path)
 }
 
-fn create_done_marker_path() -> PathBuf {
-    let nonce = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let mut path = std::env::temp_dir();
-    path.push(format!("uvessel-install-done-{nonce}.flag"));
-    path
+/// Temp files shared with the installer UI process.
+struct UiFiles {
+    done: PathBuf,
+    launch: PathBuf,
+    preflight: PathBuf,
+    close: PathBuf,
+    close_decision: PathBuf,
+    cancel: PathBuf,
+    progress: PathBuf,
+    diagnose: PathBuf,
+    diagnose_result: PathBuf,
 }
 
-fn create_log_path() -> PathBuf {
-    let nonce = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let mut path = std::env::temp_dir();
-    path.push(format!("uvessel-install-log-{nonce}.txt"));
-    path
-}
+impl UiFiles {
+    fn create() -> Self {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir();
+        Self {
+            done: dir.join(format!("uvessel-install-done-{nonce}.flag")),
+            launch: dir.join(format!("uvessel-install-launch-{nonce}.flag")),
+            preflight: dir.join(format!("uvessel-install-preflight-{nonce}.json")),
+            close: dir.join(format!("uvessel-install-close-{nonce}.json")),
+            close_decision: dir.join(format!("uvessel-install-close-decision-{nonce}.flag")),
+            cancel: dir.join(format!("uvessel-install-cancel-{nonce}.flag")),
+            progress: dir.join(format!("uvessel-install-progress-{nonce}.json")),
+            diagnose: dir.join(format!("uvessel-install-diagnose-{nonce}.flag")),
+            diagnose_result: dir.join(format!("uvessel-install-diagnose-{nonce}.json")),
+        }
+    }
 
-fn create_launch_marker_path() -> PathBuf {
-    let nonce = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let mut path = std::env::temp_dir();
-    path.push(format!("uvessel-install-launch-{nonce}.flag"));
-    path
+    fn cleanup(&self) {
+        for path in [
+            &self.done,
+            &self.launch,
+            &self.preflight,
+            &self.close,
+            &self.close_decision,
+            &self.cancel,
+            &self.progress,
+            &self.diagnose,
+            &self.diagnose_result,
+        ] {
+            let _ = fs::remove_file(path);
+        }
+    }
 }
 
-fn init_log_file(path: &Path) -> Result<()> {
-    fs::write(path, "installer log start\n")
-        .with_context(|| format!("write {}", path.display()))?;
-    Ok(())
-}
+fn exec_with_log(
+    cmd: &mut Command,
+    timeout: Duration,
+    log: &InstallLog,
+    cancel: &CancelToken,
+    progress: &ProgressReporter,
+) -> Result<CommandOutput> {
+    let _ = log.info(&format!("> {}", format_command(cmd)));
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd.spawn().context("spawn command")?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    std::thread::scope(|scope| {
+        let stdout = stdout.map(|pipe| scope.spawn(|| stream_pipe(pipe, log, progress)));
+        let stderr = stderr.map(|pipe| scope.spawn(|| stream_pipe(pipe, log, progress)));
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().context("wait for command")? {
+                break Some(status);
+            }
+            if start.elapsed() >= timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = log.warn(&format!(
+                    "command timed out after {}s, killed",
+                    timeout.as_secs()
+                ));
+                break None;
+            }
+            if cancel.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = log.warn("command aborted: install cancelled");
+                return Err(cancel::Cancelled.into());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
 
-fn log_line(path: Option<&Path>, line: &str) -> Result<()> {
-    let Some(path) = path else {
-        return Ok(());
-    };
-    use std::io::Write;
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .with_context(|| format!("open {}", path.display()))?;
-    writeln!(file, "{line}")?;
-    Ok(())
+        if let Some(handle) = stdout {
+            let _ = handle.join();
+        }
+        let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+        let _ = log.info(&format!(
+            "exit status: {}",
+            status
+                .and_then(|s| s.code())
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        ));
+        Ok(CommandOutput { status, stderr })
+    })
 }
 
-fn exec_with_log(cmd: &mut Command, log_path: Option<&Path>) -> Result<ExitStatus> {
-    if let Some(log_path) = log_path {
-        let line = format!("> {}", format_command(cmd));
-        let _ = log_line(Some(log_path), &line);
-    }
-    let output = cmd.output().context("spawn command")?;
-    if let Some(log_path) = log_path {
-        if !output.stdout.is_empty() {
-            let text = String::from_utf8_lossy(&output.stdout);
-            let _ = log_line(Some(log_path), text.trim_end());
+/// Appends each line to the install log as it arrives and returns everything read.
+fn stream_pipe(
+    pipe: impl std::io::Read,
+    log: &InstallLog,
+    progress: &ProgressReporter,
+) -> String {
+    use std::io::BufRead;
+    let mut reader = std::io::BufReader::new(pipe);
+    let mut captured = String::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
         }
-        if !output.stderr.is_empty() {
-            let text = String::from_utf8_lossy(&output.stderr);
-            let _ = log_line(Some(log_path), text.trim_end());
-        }
-        let _ = log_line(
-            Some(log_path),
-            &format!(
-                "exit status: {}",
-                output.status.code().map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string())
-            ),
-        );
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end();
+        let _ = log.info(line);
+        progress.uv_line(line);
+        captured.push_str(line);
+        captured.push('\n');
     }
-    Ok(output.status)
+    captured
 }
 
 fn format_command(cmd: &Command) -> String {
@@ -508,6 +786,77 @@ fn format_command(cmd: &Command) -> String {
     }
 }
 
+fn ensure_app_closed(
+    install_root: &Path,
+    app_name: &str,
+    close_path: Option<&Path>,
+    decision_path: Option<&Path>,
+    log: &InstallLog,
+) -> Result<()> {
+    let dest_exe = install_root.join(format!("{app_name}.exe"));
+    let running = processes::find_running(install_root);
+    if running.is_empty() && !processes::file_is_locked(&dest_exe) {
+        return Ok(());
+    }
+
+    log.info(&format!("{app_name} is running, asking it to close"))?;
+    write_close_status(close_path, "closing", &running)?;
+    processes::request_close(&running);
+    let mut remaining = processes::wait_for_exit(install_root, CLOSE_TIMEOUT);
+
+    if !remaining.is_empty() {
+        log.warn(&format!(
+            "{} process(es) still running after {}s",
+            remaining.len(),
+            CLOSE_TIMEOUT.as_secs()
+        ))?;
+        write_close_status(close_path, "timeout", &remaining)?;
+        if !wait_for_force_close(decision_path) {
+            bail!("{app_name} is still running, close it and retry");
+        }
+        log.warn(&format!("Force closing {app_name}"))?;
+        processes::force_close(&remaining);
+        remaining = processes::wait_for_exit(install_root, FORCE_CLOSE_TIMEOUT);
+        if !remaining.is_empty() {
+            bail!("could not close {app_name} ({} process(es) left)", remaining.len());
+        }
+    }
+
+    if !processes::wait_for_unlocked(&dest_exe, FORCE_CLOSE_TIMEOUT) {
+        bail!("{} is still in use", dest_exe.display());
+    }
+    write_close_status(close_path, "closed", &[])?;
+    log.info(&format!("{app_name} closed"))?;
+    Ok(())
+}
+
+fn write_close_status(
+    path: Option<&Path>,
+    status: &str,
+    running: &[processes::RunningProcess],
+) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let contents = serde_json::json!({ "state": status, "processes": running });
+    fs::write(path, contents.to_string()).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+fn wait_for_force_close(decision_path: Option<&Path>) -> bool {
+    let Some(path) = decision_path else {
+        return false;
+    };
+    let start = Instant::now();
+    while start.elapsed() < CLOSE_DECISION_TIMEOUT {
+        if let Ok(contents) = fs::read_to_string(path) {
+            return contents.trim() == "force";
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    false
+}
+
 fn ensure_uninstaller(install_root: &Path) -> Result<PathBuf> {
     let dest = install_root.join("uninstaller.exe");
     let current_exe = std::env::current_exe().context("resolve current exe")?;
@@ -519,18 +868,21 @@ fn ensure_uninstaller(install_root: &Path) -> Result<PathBuf> {
 }
 
 struct UpgradeBackup {
+    install_root: PathBuf,
     app_backup: Option<PathBuf>,
-    venv_backup: Option<PathBuf>,
+    /// Each project venv (`.runtime/venv*`) with its backup.
+    venv_backups: Vec<(PathBuf, PathBuf)>,
+    /// Each shim with its backup, or `None` when the shim is new in this install.
+    shim_backups: Vec<(PathBuf, Option<PathBuf>)>,
 }
 
 impl UpgradeBackup {
-    fn create(install_root: &Path) -> Result<Self> {
+    fn create(install_root: &Path, shims: &[PathBuf]) -> Result<Self> {
         let nonce = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_nanos();
         let app_dir = install_root.join("app");
-        let venv_dir = install_root.join(".runtime").join("venv");
         let app_backup = if app_dir.exists() {
             let backup = install_root.join(format!("app.backup.{nonce}"));
             fs::rename(&app_dir, &backup)
@@ -539,38 +891,56 @@ impl UpgradeBackup {
         } else {
             None
         };
-        let venv_backup = if venv_dir.exists() {
-            let backup = install_root.join(format!("venv.backup.{nonce}"));
+        let mut venv_backups = Vec::new();
+        for venv_dir in existing_venvs(&install_root.join(".runtime"))? {
+            let dir_name = venv_dir.file_name().unwrap_or_default().to_string_lossy();
+            let backup = install_root.join(format!("{dir_name}.backup.{nonce}"));
             fs::rename(&venv_dir, &backup)
                 .with_context(|| format!("rename {} -> {}", venv_dir.display(), backup.display()))?;
-            Some(backup)
-        } else {
-            None
-        };
+            venv_backups.push((venv_dir, backup));
+        }
+        let mut shim_backups = Vec::new();
+        for (i, shim_exe) in shims.iter().enumerate() {
+            let backup = if shim_exe.exists() {
+                let backup = install_root.join(format!("shim.backup.{nonce}.{i}"));
+                fs::rename(shim_exe, &backup).with_context(|| {
+                    format!("rename {} -> {}", shim_exe.display(), backup.display())
+                })?;
+                Some(backup)
+            } else {
+                None
+            };
+            shim_backups.push((shim_exe.clone(), backup));
+        }
         Ok(Self {
+            install_root: install_root.to_path_buf(),
             app_backup,
-            venv_backup,
+            venv_backups,
+            shim_backups,
         })
     }
 
+    /// Puts the backed up files back, discarding whatever the failed install left in their place.
     fn restore(&mut self) -> Result<()> {
         if let Some(backup) = self.app_backup.take() {
-            let target = backup.parent().unwrap_or_else(|| Path::new(".")).join("app");
-            if !target.exists() {
-                fs::rename(&backup, &target).with_context(|| {
-                    format!("restore {} -> {}", backup.display(), target.display())
-                })?;
-            }
+            let target = self.install_root.join("app");
+            restore_path(&backup, &target)?;
         }
-        if let Some(backup) = self.venv_backup.take() {
-            let target = backup.parent().unwrap_or_else(|| Path::new(".")).join(".runtime").join("venv");
-            if let Some(parent) = target.parent() {
-                fs::create_dir_all(parent)?;
+        let runtime = self.install_root.join(".runtime");
+        fs::create_dir_all(&runtime)?;
+        // Venvs of projects added by the failed install have no backup to go back to.
+        for venv_dir in existing_venvs(&runtime)? {
+            if !self.venv_backups.iter().any(|(target, _)| *target == venv_dir) {
+                remove_path(&venv_dir)?;
             }
-            if !target.exists() {
-                fs::rename(&backup, &target).with_context(|| {
-                    format!("restore {} -> {}", backup.display(), target.display())
-                })?;
+        }
+        for (target, backup) in self.venv_backups.drain(..) {
+            restore_path(&backup, &target)?;
+        }
+        for (shim_exe, backup) in self.shim_backups.drain(..) {
+            match backup {
+                Some(backup) => restore_path(&backup, &shim_exe)?,
+                None => remove_path(&shim_exe)?,
             }
         }
         Ok(())
@@ -583,16 +953,38 @@ impl UpgradeBackup {
                     .with_context(|| format!("remove {}", backup.display()))?;
             }
         }
-        if let Some(backup) = self.venv_backup.take() {
+        for (_, backup) in self.venv_backups.drain(..) {
             if backup.exists() {
                 fs::remove_dir_all(&backup)
                     .with_context(|| format!("remove {}", backup.display()))?;
             }
         }
+        for backup in self.shim_backups.drain(..).filter_map(|(_, backup)| backup) {
+            if backup.exists() {
+                fs::remove_file(&backup)
+                    .with_context(|| format!("remove {}", backup.display()))?;
+            }
+        }
         Ok(())
     }
 }
 
+fn restore_path(backup: &Path, target: &Path) -> Result<()> {
+    remove_path(target)?;
+    fs::rename(backup, target)
+        .with_context(|| format!("restore {} -> {}", backup.display(), target.display()))?;
+    Ok(())
+}
+
+fn remove_path(target: &Path) -> Result<()> {
+    if target.is_dir() {
+        fs::remove_dir_all(target).with_context(|| format!("remove {}", target.display()))?;
+    } else if target.exists() {
+        fs::remove_file(target).with_context(|| format!("remove {}", target.display()))?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Copy)]
 enum VersionRelation {
     Same,
@@ -625,32 +1017,61 @@ fn ensure_runtime_dirs(runtime: &Path) -> Result<()> {
     Ok(())
 }
 
-fn find_project(root: &Path) -> Result<PathBuf> {
-    let app_dir = root.join("app");
-    let entries = fs::read_dir(&app_dir)
-        .with_context(|| format!("read_dir {}", app_dir.display()))?;
-
-    for ent in entries {
+/// Project venvs under `.runtime`: `venv` and every `venv-<name>`.
+fn existing_venvs(runtime: &Path) -> Result<Vec<PathBuf>> {
+    if !runtime.exists() {
+        return Ok(Vec::new());
+    }
+    let mut venvs = Vec::new();
+    for ent in fs::read_dir(runtime).with_context(|| format!("read_dir {}", runtime.display()))? {
         let ent = ent?;
-        if !ent.file_type()?.is_dir() {
-            continue;
-        }
-        let candidate = ent.path();
-        if candidate.join("pyproject.toml").exists() {
-            return Ok(candidate);
+        let name = ent.file_name().to_string_lossy().to_string();
+        if ent.file_type()?.is_dir() && (name == "venv" || name.starts_with("venv-")) {
+            venvs.push(ent.path());
         }
     }
+    venvs.sort();
+    Ok(venvs)
+}
+
+/// Resolves the main project (`project` in config.toml) and the extra `[projects]` the way
+/// the builder does; the main project comes first.
+fn resolve_projects(root: &Path, main: &str, extra: &[(&str, &str)]) -> Result<Vec<state::ProjectDir>> {
+    let extra = extra
+        .iter()
+        .map(|(name, rel)| (name.to_string(), rel.to_string()))
+        .collect();
+    let mut dirs =
+        declared_scripts::project_dirs(main, &extra, root).map_err(anyhow::Error::msg)?;
+    let main_dir = dirs
+        .remove(declared_scripts::MAIN_PROJECT)
+        .context("no main project resolved")?;
+    let mut projects = vec![state::ProjectDir {
+        name: state::MAIN_PROJECT.to_string(),
+        dir: main_dir,
+    }];
+    projects.extend(dirs.into_iter().map(|(name, dir)| state::ProjectDir { name, dir }));
+    Ok(projects)
+}
 
-    bail!(
-        "No project found: expected app/<project>/pyproject.toml under {}",
-        app_dir.display()
+/// The `i`th of `n` equal slices of a progress range.
+fn split_range(start: u8, end: u8, i: usize, n: usize) -> (u8, u8) {
+    let span = (end - start) as usize;
+    let n = n.max(1);
+    (
+        start + (span * i / n) as u8,
+        start + (span * (i + 1) / n) as u8,
     )
 }
 
-fn build_uv_cmd(uv: &Path, proj: &Path, runtime: &Path) -> Command {
+fn build_uv_cmd(uv: &Path, proj: &Path, runtime: &Path, venv: &Path) -> Command {
     let mut c = Command::new(uv);
+    // The same policy as the launcher's, so a sync that works here also works at launch.
+    for key in env_policy::stripped_vars(std::env::vars_os().map(|(key, _)| key)) {
+        c.env_remove(key);
+    }
     c.current_dir(proj)
-        .envs(uv_env_pairs(runtime))
+        .envs(uv_env_pairs(runtime, venv))
         .stdin(Stdio::null());
     #[cfg(windows)]
     {
@@ -661,12 +1082,12 @@ fn build_uv_cmd(uv: &Path, proj: &Path, runtime: &Path) -> Command {
     c
 }
 
-fn uv_env_pairs(runtime: &Path) -> Vec<(String, String)> {
+fn uv_env_pairs(runtime: &Path, venv: &Path) -> Vec<(String, String)> {
     vec![
         ("UV_CACHE_DIR".to_string(), runtime.join("cache").to_string_lossy().to_string()),
         ("UV_PYTHON_INSTALL_DIR".to_string(), runtime.join("python").to_string_lossy().to_string()),
         ("UV_PYTHON_BIN_DIR".to_string(), runtime.join("python-bin").to_string_lossy().to_string()),
-        ("UV_PROJECT_ENVIRONMENT".to_string(), runtime.join("venv").to_string_lossy().to_string()),
+        ("UV_PROJECT_ENVIRONMENT".to_string(), venv.to_string_lossy().to_string()),
         ("UV_TOOL_DIR".to_string(), runtime.join("tools").to_string_lossy().to_string()),
         ("UV_TOOL_BIN_DIR".to_string(), runtime.join("tool-bin").to_string_lossy().to_string()),
         ("UV_NO_CONFIG".to_string(), "1".to_string()),
@@ -700,11 +1121,63 @@ fn resolve_icon_path(install_root: &Path) -> Option<PathBuf> {
     candidate.exists().then_some(candidate)
 }
 
-fn write_shim_exe(dest_exe: &Path) -> Result<()> {
-    if shim_payload::EMBEDDED_SHIM.is_empty() {
-        bail!("embedded shim is empty");
+/// A launcher shim the installer writes: the app's own exe, plus one per named entry point.
+struct ShimTarget {
+    path: PathBuf,
+    console: bool,
+    entry: Option<&'static crate::config::NamedEntryPoint>,
+}
+
+fn shim_targets(install_root: &Path, app_name: &str) -> Vec<ShimTarget> {
+    let mut shims = vec![ShimTarget {
+        path: install_root.join(format!("{app_name}.exe")),
+        console: crate::config::CONSOLE,
+        entry: None,
+    }];
+    shims.extend(crate::config::ENTRY_POINTS.iter().map(|named| ShimTarget {
+        path: install_root.join(format!("{}.exe", named.name)),
+        console: named.console,
+        entry: Some(named),
+    }));
+    shims
+}
+
+fn write_shim_exe(shim: &ShimTarget) -> Result<()> {
+    let bytes = shim_payload::shim_bytes(shim.console, shim.entry.map(|e| e.name))?;
+    fs_ops::write_bytes_with_retry(&shim.path, &bytes, 5)
+}
+
+/// Removes the shims and Start menu shortcuts of entry points the previous version had but
+/// this one dropped; `shims` and `shortcuts` are what this install wrote.
+fn remove_stale_entries(
+    install_root: &Path,
+    start_menu: Option<&Path>,
+    previous: &state::State,
+    shims: &[ShimTarget],
+    shortcuts: &[String],
+) {
+    for old in &previous.entry_points {
+        let path = install_root.join(format!("{}.exe", old.name));
+        if !shims.iter().any(|shim| shim.path == path) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+    let Some(start_menu) = start_menu else {
+        return;
+    };
+    for name in &previous.shortcuts {
+        if !shortcuts.contains(name) {
+            let _ = shortcuts::remove_start_menu_shortcut(start_menu, name);
+        }
+    }
+}
+
+fn entry_shortcut_name(app_name: &str, named: &crate::config::NamedEntryPoint) -> String {
+    if named.shortcut_name.trim().is_empty() {
+        format!("{app_name} {}", named.name)
+    } else {
+        named.shortcut_name.trim().to_string()
     }
-    fs_ops::write_bytes_with_retry(dest_exe, shim_payload::EMBEDDED_SHIM, 5)
 }
 
 fn cleanup_uv_cache(runtime: &Path) -> Result<()> {
@@ -717,26 +1190,32 @@ fn cleanup_uv_cache(runtime: &Path) -> Result<()> {
 }
 
 fn run_with_retry(
-    mut make_status: impl FnMut() -> Result<ExitStatus>,
-    attempts: usize,
+    mut make_output: impl FnMut() -> Result<CommandOutput>,
+    policy: &RetryPolicy,
     label: &str,
+    log: &InstallLog,
 ) -> Result<()> {
-    let mut delay = std::time::Duration::from_millis(250);
-    for i in 0..attempts {
-        let status = make_status()?;
-        if status.success() {
+    let mut delay = policy.initial_delay;
+    for i in 0..policy.attempts {
+        let output = make_output()?;
+        if output.success() {
             return Ok(());
         }
-        if i + 1 == attempts {
-            eprintln!("warning: {label} failed after {attempts} attempts");
-            bail!("{label} failed (exit {:?})", status.code());
+        let kind = output.failure_kind();
+        if !kind.is_retryable() {
+            let _ = log.error(&format!("{label} failed ({kind}), not retrying"));
+            bail!("{label} failed ({kind}): {}", output.error_summary());
+        }
+        if i + 1 == policy.attempts {
+            let _ = log.error(&format!("{label} failed after {} attempts", policy.attempts));
+            bail!("{label} failed ({kind}): {}", output.error_summary());
         }
-        eprintln!(
-            "warning: {label} failed (exit {:?}), retrying...",
-            status.code()
-        );
+        let _ = log.warn(&format!(
+            "{label} failed ({kind}), retrying in {}ms...",
+            delay.as_millis()
+        ));
         std::thread::sleep(delay);
-        delay = std::cmp::min(delay * 2, std::time::Duration::from_secs(5));
+        delay = std::cmp::min(delay * 2, policy.max_delay);
     }
     Ok(())
 }
@@ -746,16 +1225,157 @@ mod tests {
     use super::*;
 
     #[test]
-    fn app_name_from_exe_rejects_empty() {
-        let err = app_name_from_exe(Path::new("")).unwrap_err();
-        assert!(err.to_string().contains("executable name is empty"));
+    fn uv_env_pairs_include_no_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let runtime = tmp.path().join(".runtime");
+        let envs = uv_env_pairs(&runtime, &runtime.join("venv"));
+        assert!(envs.iter().any(|(k, v)| k == "UV_NO_CONFIG" && v == "1"));
     }
 
     #[test]
-    fn uv_env_pairs_include_no_config() {
+    fn upgrade_backup_restore_replaces_partial_install() {
         let tmp = tempfile::tempdir().unwrap();
-        let envs = uv_env_pairs(&tmp.path().join(".runtime"));
-        assert!(envs.iter().any(|(k, v)| k == "UV_NO_CONFIG" && v == "1"));
+        let root = tmp.path();
+        let shim = root.join("MyApp.exe");
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(root.join("app").join("old.txt"), "old").unwrap();
+        fs::write(&shim, "old shim").unwrap();
+        let venv = root.join(".runtime").join("venv");
+        fs::create_dir_all(&venv).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "old").unwrap();
+
+        let added = root.join("cli.exe");
+        let mut backup = UpgradeBackup::create(root, &[shim.clone(), added.clone()]).unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(root.join("app").join("new.txt"), "new").unwrap();
+        fs::write(&shim, "new shim").unwrap();
+        fs::write(&added, "new cli shim").unwrap();
+        let added_venv = root.join(".runtime").join("venv-sidecar");
+        fs::create_dir_all(&added_venv).unwrap();
+        fs::create_dir_all(&venv).unwrap();
+
+        backup.restore().unwrap();
+        assert_eq!(fs::read_to_string(venv.join("pyvenv.cfg")).unwrap(), "old");
+        assert!(!added_venv.exists());
+        assert!(root.join("app").join("old.txt").exists());
+        assert!(!root.join("app").join("new.txt").exists());
+        assert_eq!(fs::read_to_string(&shim).unwrap(), "old shim");
+        assert!(!added.exists());
+    }
+
+    #[test]
+    fn resolve_projects_requires_explicit_main_when_ambiguous() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for dir in ["app/gui", "app/tool"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("pyproject.toml"), "").unwrap();
+        }
+
+        let err = resolve_projects(root, "", &[]).unwrap_err();
+        assert!(err.to_string().contains("set project"));
+
+        // A project claimed by [projects] is not a main project candidate.
+        let projects = resolve_projects(root, "", &[("tool", "app/tool")]).unwrap();
+        assert_eq!(projects[0].name, state::MAIN_PROJECT);
+        assert_eq!(projects[0].dir, root.join("app/gui"));
+        assert_eq!(projects[1].name, "tool");
+
+        let projects = resolve_projects(root, "app/tool", &[]).unwrap();
+        assert_eq!(projects[0].dir, root.join("app/tool"));
+        assert!(resolve_projects(root, "app/missing", &[]).is_err());
+    }
+
+    #[test]
+    fn run_with_retry_stops_on_fatal_failure() {
+        let policy = RetryPolicy {
+            attempts: 5,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            python_install_timeout: Duration::from_secs(1),
+            sync_timeout: Duration::from_secs(1),
+        };
+        let mut calls = 0;
+        let err = run_with_retry(
+            || {
+                calls += 1;
+                if calls == 1 {
+                    return Ok(CommandOutput {
+                        status: None,
+                        stderr: String::new(),
+                    });
+                }
+                Ok(CommandOutput {
+                    status: Some(failed_status()),
+                    stderr: "No solution found when resolving dependencies".to_string(),
+                })
+            },
+            &policy,
+            "uv sync",
+            &InstallLog::default(),
+        )
+        .unwrap_err();
+        assert_eq!(calls, 2);
+        assert!(err.to_string().contains("resolution error"));
+    }
+
+    #[test]
+    fn upgrade_removes_dropped_shims_and_shortcuts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("MyApp");
+        let start_menu = tmp.path().join("Programs");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&start_menu).unwrap();
+        fs::write(root.join("old-tool.exe"), b"shim").unwrap();
+        for name in ["MyApp", "MyApp Old Tool"] {
+            fs::write(start_menu.join(format!("{name}.lnk")), b"lnk").unwrap();
+        }
+        let mut previous: state::State = serde_json::from_str(
+            r#"{"entry": {"kind": "python_file", "value": "main.py"}, "installed": true}"#,
+        )
+        .unwrap();
+        previous.entry_points.push(state::NamedEntry {
+            name: "old-tool".to_string(),
+            entry: state::EntryPoint::Module("app.tool".to_string()),
+            args: Vec::new(),
+            project: state::MAIN_PROJECT.to_string(),
+        });
+        previous.shortcuts = vec!["MyApp".to_string(), "MyApp Old Tool".to_string()];
+
+        let shims = shim_targets(&root, "MyApp");
+        remove_stale_entries(&root, Some(&start_menu), &previous, &shims, &["MyApp".to_string()]);
+        assert!(!root.join("old-tool.exe").exists());
+        assert!(!start_menu.join("MyApp Old Tool.lnk").exists());
+        assert!(start_menu.join("MyApp.lnk").exists());
+    }
+
+    #[test]
+    fn fresh_install_rollback_keeps_logs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("MyApp");
+        let log = InstallLog::open("MyApp");
+        fs::create_dir_all(root.join(".runtime").join("venv")).unwrap();
+        keep_log_in(&log, &root);
+        fs::write(root.join("MyApp.exe"), b"shim").unwrap();
+
+        remove_install_keeping_logs(&root).unwrap();
+        assert!(!root.join("MyApp.exe").exists());
+        assert!(!root.join(".runtime").join("venv").exists());
+        assert!(log.path().unwrap().exists());
+        log.finish();
+    }
+
+    fn failed_status() -> std::process::ExitStatus {
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::ExitStatusExt;
+            std::process::ExitStatus::from_raw(1)
+        }
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            std::process::ExitStatus::from_raw(256)
+        }
     }
 
     #[test]
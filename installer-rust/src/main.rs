@@ -1,12 +1,35 @@
 #![cfg_attr(windows, windows_subsystem = "windows")]
 
+mod cancel;
+// Shared with the builder, so both agree on which projects exist.
+#[allow(dead_code)]
+#[path = "../../builder-rust/src/declared_scripts.rs"]
+mod declared_scripts;
+#[path = "../../launcher-rust/src/diagnostics.rs"]
+mod diagnostics;
+mod entry_spec;
+#[path = "../../launcher-rust/src/env_lock.rs"]
+mod env_lock;
+#[path = "../../launcher-rust/src/env_policy.rs"]
+mod env_policy;
 mod fs_ops;
+mod install_log;
 mod installer;
+// Only the Windows running-instance check uses it.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[path = "../../launcher-rust/src/instance_name.rs"]
+mod instance_name;
 mod paths;
 mod payload;
+mod preflight;
+mod processes;
+mod progress;
+mod retry;
 mod shortcuts;
 mod shim_payload;
 mod state;
+#[path = "../../launcher-rust/src/timestamp.rs"]
+mod timestamp;
 mod uv;
 mod config;
 mod ui_payload;
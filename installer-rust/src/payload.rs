@@ -36,6 +36,44 @@ pub fn install_payload_with_options(dest_root: &Path, options: PayloadOptions) -
     extract_zip_to(dest_root, options)
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PayloadStats {
+    pub total_bytes: u64,
+    pub longest_path: usize,
+}
+
+pub fn payload_stats() -> Result<PayloadStats> {
+    let reader = std::io::Cursor::new(EMBEDDED_PAYLOAD);
+    let mut zip = zip::ZipArchive::new(reader).context("read embedded zip")?;
+    let mut stats = PayloadStats::default();
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i)?;
+        stats.total_bytes += entry.size();
+        stats.longest_path = stats.longest_path.max(entry.name().len());
+    }
+    Ok(stats)
+}
+
+/// Records the hash of every `app/` file in the payload at `path`, so an install can be
+/// checked against it later; `data/` and `assets/` belong to the user once installed.
+pub fn write_manifest(path: &Path, hash: impl Fn(&[u8]) -> String) -> Result<()> {
+    let reader = std::io::Cursor::new(EMBEDDED_PAYLOAD);
+    let mut zip = zip::ZipArchive::new(reader).context("read embedded zip")?;
+    let mut manifest = std::collections::BTreeMap::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if entry.is_dir() || !entry.name().starts_with("app/") {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut buf = Vec::new();
+        entry.read_to_end(&mut buf)?;
+        manifest.insert(name, hash(&buf));
+    }
+    let json = serde_json::to_string_pretty(&manifest)?;
+    std::fs::write(path, json).with_context(|| format!("write {}", path.display()))
+}
+
 pub fn extract_embedded_file(path: &Path) -> Result<Option<std::path::PathBuf>> {
     if path.is_absolute()
         || path
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::diagnostics::free_space_bytes;
+use crate::{payload, processes};
+
+#[cfg(windows)]
+const MAX_PATH_LEN: usize = 260;
+#[cfg(not(windows))]
+const MAX_PATH_LEN: usize = 4096;
+
+// Room left for the deepest paths uv writes under .runtime\venv (site-packages, dist-info).
+const VENV_PATH_HEADROOM: usize = 120;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl PreflightCheck {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+
+    pub fn summary(&self) -> String {
+        let failed: Vec<String> = self
+            .checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .map(|c| format!("{}: {}", c.name, c.message))
+            .collect();
+        if failed.is_empty() {
+            "all checks passed".to_string()
+        } else {
+            failed.join("; ")
+        }
+    }
+}
+
+pub fn run_checks(install_root: &Path, app_name: &str) -> PreflightReport {
+    let stats = payload::payload_stats().unwrap_or_default();
+    let required = stats.total_bytes + crate::config::VENV_SIZE_ESTIMATE_BYTES;
+    PreflightReport {
+        checks: vec![
+            check_disk_space(install_root, required),
+            check_write_access(install_root),
+            check_path_length(install_root, stats.longest_path),
+            check_running_instance(install_root, app_name),
+        ],
+    }
+}
+
+pub fn write_report(path: &Path, report: &PreflightReport) -> Result<()> {
+    let contents = serde_json::to_string_pretty(report).context("serialize preflight report")?;
+    fs::write(path, contents).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+fn check_disk_space(install_root: &Path, required: u64) -> PreflightCheck {
+    let Some(dir) = existing_ancestor(install_root) else {
+        return PreflightCheck::new(
+            "disk_space",
+            CheckStatus::Warn,
+            format!("no existing parent for {}", install_root.display()),
+        );
+    };
+    match free_space_bytes(&dir) {
+        Some(free) if free < required => PreflightCheck::new(
+            "disk_space",
+            CheckStatus::Fail,
+            format!(
+                "{} free on {}, {} required",
+                format_mb(free),
+                dir.display(),
+                format_mb(required)
+            ),
+        ),
+        Some(free) => PreflightCheck::new(
+            "disk_space",
+            CheckStatus::Pass,
+            format!("{} free, {} required", format_mb(free), format_mb(required)),
+        ),
+        None => PreflightCheck::new(
+            "disk_space",
+            CheckStatus::Warn,
+            format!("could not determine free space on {}", dir.display()),
+        ),
+    }
+}
+
+fn check_write_access(install_root: &Path) -> PreflightCheck {
+    let Some(dir) = existing_ancestor(install_root) else {
+        return PreflightCheck::new(
+            "write_access",
+            CheckStatus::Fail,
+            format!("no existing parent for {}", install_root.display()),
+        );
+    };
+    match tempfile::Builder::new()
+        .prefix(".uvessel-preflight-")
+        .tempfile_in(&dir)
+    {
+        Ok(_) => PreflightCheck::new(
+            "write_access",
+            CheckStatus::Pass,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(err) => PreflightCheck::new(
+            "write_access",
+            CheckStatus::Fail,
+            format!("cannot write to {}: {err}", dir.display()),
+        ),
+    }
+}
+
+fn check_path_length(install_root: &Path, longest_payload_path: usize) -> PreflightCheck {
+    let root_len = install_root.as_os_str().len() + 1;
+    let payload_len = root_len + longest_payload_path;
+    let venv_len = root_len + VENV_PATH_HEADROOM;
+    if payload_len >= MAX_PATH_LEN {
+        PreflightCheck::new(
+            "path_length",
+            CheckStatus::Fail,
+            format!("payload paths reach {payload_len} characters (limit {MAX_PATH_LEN})"),
+        )
+    } else if venv_len >= MAX_PATH_LEN {
+        PreflightCheck::new(
+            "path_length",
+            CheckStatus::Warn,
+            format!(
+                "install path leaves little room for venv paths (limit {MAX_PATH_LEN}), choose a shorter install_dir"
+            ),
+        )
+    } else {
+        PreflightCheck::new(
+            "path_length",
+            CheckStatus::Pass,
+            format!("longest path {} characters", payload_len.max(venv_len)),
+        )
+    }
+}
+
+fn check_running_instance(install_root: &Path, app_name: &str) -> PreflightCheck {
+    if processes::is_app_running(install_root, app_name) {
+        return PreflightCheck::new(
+            "running_instance",
+            CheckStatus::Warn,
+            format!("{app_name} is running and will be asked to close"),
+        );
+    }
+    PreflightCheck::new("running_instance", CheckStatus::Pass, "no running instance")
+}
+
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.is_dir()).map(Path::to_path_buf)
+}
+
+fn format_mb(bytes: u64) -> String {
+    format!("{} MB", bytes / (1024 * 1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_fails_when_any_check_fails() {
+        let mut report = PreflightReport {
+            checks: vec![PreflightCheck::new("disk_space", CheckStatus::Warn, "unknown")],
+        };
+        assert!(report.passed());
+
+        report
+            .checks
+            .push(PreflightCheck::new("write_access", CheckStatus::Fail, "denied"));
+        assert!(!report.passed());
+        assert_eq!(report.summary(), "write_access: denied");
+    }
+
+    #[test]
+    fn path_length_fails_when_payload_exceeds_limit() {
+        let root = PathBuf::from("C:\\Apps\\MyApp");
+        let check = check_path_length(&root, MAX_PATH_LEN);
+        assert_eq!(check.status, CheckStatus::Fail);
+
+        let check = check_path_length(&root, 40);
+        assert_eq!(check.status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn write_access_checks_nearest_existing_parent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let check = check_write_access(&tmp.path().join("missing").join("MyApp"));
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+}
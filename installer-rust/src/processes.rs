@@ -0,0 +1,241 @@
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunningProcess {
+    pub pid: u32,
+    pub path: PathBuf,
+}
+
+/// True when a launcher's instance mutex is held or any process runs from the install root.
+pub fn is_app_running(install_root: &Path, app_name: &str) -> bool {
+    let exe = install_root.join(format!("{app_name}.exe"));
+    instance_mutex_exists() || file_is_locked(&exe) || !find_running(install_root).is_empty()
+}
+
+pub fn find_running(install_root: &Path) -> Vec<RunningProcess> {
+    let own_pid = std::process::id();
+    list_processes()
+        .into_iter()
+        .filter(|p| p.pid != own_pid && path_is_under(&p.path, install_root))
+        .collect()
+}
+
+pub fn wait_for_exit(install_root: &Path, timeout: Duration) -> Vec<RunningProcess> {
+    let start = Instant::now();
+    loop {
+        let remaining = find_running(install_root);
+        if remaining.is_empty() || start.elapsed() >= timeout {
+            return remaining;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+pub fn wait_for_unlocked(path: &Path, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while file_is_locked(path) {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    true
+}
+
+pub fn file_is_locked(path: &Path) -> bool {
+    path.exists() && fs::OpenOptions::new().append(true).open(path).is_err()
+}
+
+fn path_is_under(path: &Path, root: &Path) -> bool {
+    let normalize = |p: &Path| p.to_string_lossy().replace('/', "\\").to_lowercase();
+    let root = normalize(root);
+    let root = root.trim_end_matches('\\');
+    let path = normalize(path);
+    path.len() > root.len() && path.starts_with(root) && path[root.len()..].starts_with('\\')
+}
+
+#[cfg(windows)]
+fn list_processes() -> Vec<RunningProcess> {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+    let mut ok = unsafe { Process32FirstW(snapshot, &mut entry) };
+    while ok != 0 {
+        if let Some(path) = process_image_path(entry.th32ProcessID) {
+            out.push(RunningProcess {
+                pid: entry.th32ProcessID,
+                path,
+            });
+        }
+        ok = unsafe { Process32NextW(snapshot, &mut entry) };
+    }
+    unsafe { CloseHandle(snapshot) };
+    out
+}
+
+#[cfg(windows)]
+fn process_image_path(pid: u32) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if handle == 0 {
+        return None;
+    }
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = unsafe {
+        QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut len)
+    };
+    unsafe { CloseHandle(handle) };
+    (ok != 0).then(|| PathBuf::from(OsString::from_wide(&buf[..len as usize])))
+}
+
+#[cfg(not(windows))]
+fn list_processes() -> Vec<RunningProcess> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|ent| ent.ok())
+        .filter_map(|ent| {
+            let pid = ent.file_name().to_str()?.parse::<u32>().ok()?;
+            let path = fs::read_link(ent.path().join("exe")).ok()?;
+            Some(RunningProcess { pid, path })
+        })
+        .collect()
+}
+
+/// Posts WM_CLOSE to every top-level window owned by the given processes.
+#[cfg(windows)]
+pub fn request_close(procs: &[RunningProcess]) {
+    use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowThreadProcessId, PostMessageW, WM_CLOSE,
+    };
+
+    unsafe extern "system" fn close_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let pids = &*(lparam as *const Vec<u32>);
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pids.contains(&pid) {
+            PostMessageW(hwnd, WM_CLOSE, 0, 0);
+        }
+        1
+    }
+
+    let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+    unsafe { EnumWindows(Some(close_window), &pids as *const Vec<u32> as LPARAM) };
+}
+
+#[cfg(not(windows))]
+pub fn request_close(procs: &[RunningProcess]) {
+    send_signal(procs, libc::SIGTERM);
+}
+
+#[cfg(windows)]
+pub fn force_close(procs: &[RunningProcess]) {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+
+    for proc in procs {
+        let handle = unsafe { OpenProcess(PROCESS_TERMINATE, 0, proc.pid) };
+        if handle == 0 {
+            continue;
+        }
+        unsafe {
+            TerminateProcess(handle, 1);
+            CloseHandle(handle);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn force_close(procs: &[RunningProcess]) {
+    send_signal(procs, libc::SIGKILL);
+}
+
+#[cfg(not(windows))]
+fn send_signal(procs: &[RunningProcess], signal: libc::c_int) {
+    for proc in procs {
+        unsafe { libc::kill(proc.pid as libc::pid_t, signal) };
+    }
+}
+
+#[cfg(windows)]
+fn instance_mutex_exists() -> bool {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE};
+
+    // Named entry points are separate programs with a mutex of their own.
+    let named = crate::config::ENTRY_POINTS.iter().map(|entry| Some(entry.name));
+    once(None).chain(named).any(|entry_name| {
+        let name = instance_mutex_name(entry_name);
+        let wide: Vec<u16> = OsStr::new(&name).encode_wide().chain(once(0)).collect();
+        let handle = unsafe { OpenMutexW(SYNCHRONIZATION_SYNCHRONIZE, 0, wide.as_ptr()) };
+        if handle == 0 {
+            return false;
+        }
+        unsafe { CloseHandle(handle) };
+        true
+    })
+}
+
+#[cfg(not(windows))]
+fn instance_mutex_exists() -> bool {
+    false
+}
+
+/// The `Local\` mutex a launcher holds while it runs.
+#[cfg(windows)]
+fn instance_mutex_name(entry_name: Option<&str>) -> String {
+    format!("Local\\{}", crate::instance_name::for_entry(entry_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_is_under_matches_descendants_only() {
+        let root = Path::new(r"C:\Apps\MyApp");
+        assert!(path_is_under(Path::new(r"C:\Apps\MyApp\MyApp.exe"), root));
+        assert!(path_is_under(
+            Path::new(r"c:\apps\myapp\.runtime\venv\Scripts\python.exe"),
+            root
+        ));
+        assert!(!path_is_under(Path::new(r"C:\Apps\MyAppOld\MyApp.exe"), root));
+        assert!(!path_is_under(Path::new(r"C:\Apps\MyApp"), root));
+    }
+
+    #[test]
+    fn find_running_skips_current_process() {
+        let exe = std::env::current_exe().unwrap();
+        let root = exe.parent().unwrap();
+        let running = find_running(root);
+        assert!(running.iter().all(|p| p.pid != std::process::id()));
+    }
+}
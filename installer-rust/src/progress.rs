@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    Downloading(String),
+    Downloaded(String),
+    Resolved(usize),
+    Prepared(usize),
+    Installed(usize),
+    PythonInstalled(String),
+}
+
+/// Recognizes the progress lines uv prints when its output is not a terminal.
+pub fn parse_uv_line(line: &str) -> Option<ProgressEvent> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("Downloading ") {
+        let name = rest.split(" (").next().unwrap_or(rest).trim();
+        return Some(ProgressEvent::Downloading(name.to_string()));
+    }
+    if let Some(rest) = line.strip_prefix("Downloaded ") {
+        return Some(ProgressEvent::Downloaded(rest.trim().to_string()));
+    }
+    if let Some(rest) = line.strip_prefix("Installed Python ") {
+        let version = rest.split(" in ").next().unwrap_or(rest).trim();
+        return Some(ProgressEvent::PythonInstalled(version.to_string()));
+    }
+    let mut words = line.split_whitespace();
+    let verb = words.next()?;
+    let count = words.next()?.parse::<usize>().ok()?;
+    if !words.next()?.starts_with("package") {
+        return None;
+    }
+    match verb {
+        "Resolved" => Some(ProgressEvent::Resolved(count)),
+        "Prepared" => Some(ProgressEvent::Prepared(count)),
+        "Installed" | "Audited" => Some(ProgressEvent::Installed(count)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct ProgressState {
+    phase: String,
+    detail: String,
+    percent: u8,
+    #[serde(skip)]
+    start: u8,
+    #[serde(skip)]
+    end: u8,
+    #[serde(skip)]
+    downloads_started: usize,
+    #[serde(skip)]
+    downloads_finished: usize,
+}
+
+impl ProgressState {
+    fn advance(&mut self, fraction: f32) {
+        let span = self.end.saturating_sub(self.start) as f32;
+        let percent = self.start + (span * fraction.clamp(0.0, 1.0)) as u8;
+        self.percent = self.percent.max(percent);
+    }
+
+    fn apply(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Downloading(name) => {
+                self.downloads_started += 1;
+                self.detail = format!("Downloading {name}");
+            }
+            ProgressEvent::Downloaded(name) => {
+                self.downloads_finished += 1;
+                self.detail = format!("Downloaded {name}");
+                let started = self.downloads_started.max(self.downloads_finished);
+                self.advance(0.1 + 0.7 * self.downloads_finished as f32 / started as f32);
+            }
+            ProgressEvent::Resolved(count) => {
+                self.detail = format!("Resolved {count} packages");
+                self.advance(0.1);
+            }
+            ProgressEvent::Prepared(count) => {
+                self.detail = format!("Prepared {count} packages");
+                self.advance(0.85);
+            }
+            ProgressEvent::Installed(count) => {
+                self.detail = format!("Installed {count} packages");
+                self.advance(1.0);
+            }
+            ProgressEvent::PythonInstalled(version) => {
+                self.detail = format!("Installed Python {version}");
+                self.advance(1.0);
+            }
+        }
+    }
+}
+
+/// Writes the current install phase and percentage to the file the installer UI polls.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    path: Option<PathBuf>,
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl ProgressReporter {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            state: Arc::default(),
+        }
+    }
+
+    /// Starts a phase that owns the `start..end` slice of the overall percentage.
+    pub fn phase(&self, label: &str, start: u8, end: u8) {
+        self.update(|state| {
+            state.phase = label.to_string();
+            state.detail.clear();
+            state.start = start;
+            state.end = end;
+            state.downloads_started = 0;
+            state.downloads_finished = 0;
+            state.advance(0.0);
+        });
+    }
+
+    pub fn uv_line(&self, line: &str) {
+        if let Some(event) = parse_uv_line(line) {
+            self.update(|state| state.apply(event));
+        }
+    }
+
+    pub fn finish(&self) {
+        self.update(|state| {
+            state.detail.clear();
+            state.percent = 100;
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ProgressState)) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        f(&mut state);
+        if let Some(path) = self.path.as_ref() {
+            let _ = write_progress(path, &state);
+        }
+    }
+}
+
+fn write_progress(path: &Path, state: &ProgressState) -> Result<()> {
+    let contents = serde_json::to_string(state).context("serialize progress")?;
+    fs::write(path, contents).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uv_line_recognizes_progress() {
+        assert_eq!(
+            parse_uv_line("Downloading numpy (15.2MiB)"),
+            Some(ProgressEvent::Downloading("numpy".to_string()))
+        );
+        assert_eq!(
+            parse_uv_line(" Downloaded numpy"),
+            Some(ProgressEvent::Downloaded("numpy".to_string()))
+        );
+        assert_eq!(
+            parse_uv_line("Installed Python 3.12.7 in 3.05s"),
+            Some(ProgressEvent::PythonInstalled("3.12.7".to_string()))
+        );
+        assert_eq!(
+            parse_uv_line("Resolved 25 packages in 1ms"),
+            Some(ProgressEvent::Resolved(25))
+        );
+        assert_eq!(
+            parse_uv_line("Installed 1 package in 40ms"),
+            Some(ProgressEvent::Installed(1))
+        );
+        assert_eq!(parse_uv_line(" + numpy==1.26.4"), None);
+    }
+
+    #[test]
+    fn reporter_scales_progress_into_phase() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("progress.json");
+        let progress = ProgressReporter::new(Some(path.clone()));
+        progress.phase("Syncing dependencies", 40, 90);
+        progress.uv_line("Downloading numpy (15.2MiB)");
+        progress.uv_line("Downloaded numpy");
+        progress.uv_line("Installed 3 packages in 40ms");
+
+        let out: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(out["phase"], "Syncing dependencies");
+        assert_eq!(out["percent"], 90);
+        assert_eq!(out["detail"], "Installed 3 packages");
+    }
+}
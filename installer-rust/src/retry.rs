@@ -0,0 +1,176 @@
+use std::{fmt, process::ExitStatus, time::Duration};
+
+use crate::config;
+
+/// Retry and timeout settings for uv steps, taken from the `[retry]` table in config.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: usize,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub python_install_timeout: Duration,
+    pub sync_timeout: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config() -> Self {
+        Self {
+            attempts: config::RETRY_ATTEMPTS.max(1),
+            initial_delay: Duration::from_millis(config::RETRY_INITIAL_DELAY_MS),
+            max_delay: Duration::from_millis(config::RETRY_MAX_DELAY_MS),
+            python_install_timeout: Duration::from_secs(config::PYTHON_INSTALL_TIMEOUT_SECS),
+            sync_timeout: Duration::from_secs(config::SYNC_TIMEOUT_SECS),
+        }
+    }
+}
+
+/// Result of one uv invocation. `status` is `None` when the child was killed after its timeout.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub status: Option<ExitStatus>,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.status.map(|s| s.success()).unwrap_or(false)
+    }
+
+    pub fn failure_kind(&self) -> FailureKind {
+        match self.status {
+            None => FailureKind::Timeout,
+            Some(_) => classify_failure(&self.stderr),
+        }
+    }
+
+    /// Last non-empty stderr line, which is where uv puts the actual error.
+    pub fn error_summary(&self) -> String {
+        self.stderr
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("no output")
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Network,
+    LockContention,
+    Timeout,
+    Resolution,
+    MissingWheel,
+    Unknown,
+}
+
+impl FailureKind {
+    pub fn is_retryable(self) -> bool {
+        !matches!(self, FailureKind::Resolution | FailureKind::MissingWheel)
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FailureKind::Network => "network error",
+            FailureKind::LockContention => "lock contention",
+            FailureKind::Timeout => "timed out",
+            FailureKind::Resolution => "resolution error",
+            FailureKind::MissingWheel => "no compatible wheel",
+            FailureKind::Unknown => "unknown error",
+        };
+        f.write_str(label)
+    }
+}
+
+const RESOLUTION_PATTERNS: &[&str] = &[
+    "no solution found when resolving",
+    "requirements are unsatisfiable",
+    "because there is no version of",
+    "needs to be updated, but",
+    "no download found for request",
+    "no interpreter found",
+];
+
+const MISSING_WHEEL_PATTERNS: &[&str] = &[
+    "doesn't have a source distribution or wheel",
+    "has no wheels with a matching",
+    "no wheels with a matching platform tag",
+    "is not a supported wheel on this platform",
+];
+
+const LOCK_PATTERNS: &[&str] = &[
+    "failed to acquire lock",
+    "being used by another process",
+    "os error 32",
+    "os error 33",
+    "resource busy",
+];
+
+const NETWORK_PATTERNS: &[&str] = &[
+    "failed to download",
+    "failed to fetch",
+    "error sending request",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "operation timed out",
+    "dns error",
+    "tls handshake",
+    "network unreachable",
+    "http status server error",
+    "request failed after",
+];
+
+pub fn classify_failure(stderr: &str) -> FailureKind {
+    let lowered = stderr.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|p| lowered.contains(p));
+    if matches(RESOLUTION_PATTERNS) {
+        FailureKind::Resolution
+    } else if matches(MISSING_WHEEL_PATTERNS) {
+        FailureKind::MissingWheel
+    } else if matches(LOCK_PATTERNS) {
+        FailureKind::LockContention
+    } else if matches(NETWORK_PATTERNS) {
+        FailureKind::Network
+    } else {
+        FailureKind::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_failure_separates_fatal_from_retryable() {
+        let resolution = "  × No solution found when resolving dependencies:\n  ╰─▶ Because there is no version of foo==9.9";
+        assert_eq!(classify_failure(resolution), FailureKind::Resolution);
+        assert!(!FailureKind::Resolution.is_retryable());
+
+        let wheel = "error: Distribution `torch==2.0.0` can't be installed because it doesn't have a source distribution or wheel for the current platform";
+        assert_eq!(classify_failure(wheel), FailureKind::MissingWheel);
+
+        let network = "error: Failed to download `numpy==1.26.4`\n  Caused by: error sending request for url";
+        assert_eq!(classify_failure(network), FailureKind::Network);
+        assert!(FailureKind::Network.is_retryable());
+
+        let lock = "error: failed to acquire lock on the cache";
+        assert_eq!(classify_failure(lock), FailureKind::LockContention);
+
+        assert_eq!(classify_failure("something else"), FailureKind::Unknown);
+    }
+
+    #[test]
+    fn timed_out_output_is_retryable() {
+        let output = CommandOutput {
+            status: None,
+            stderr: String::new(),
+        };
+        assert_eq!(output.failure_kind(), FailureKind::Timeout);
+        assert!(output.failure_kind().is_retryable());
+        assert_eq!(output.error_summary(), "no output");
+    }
+}
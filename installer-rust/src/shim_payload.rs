@@ -1,2 +1,54 @@
+use anyhow::{bail, Result};
+
 pub const EMBEDDED_SHIM: &[u8] =
     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/embedded/launcher.exe"));
+
+/// Console-subsystem shim; empty when no entry point sets `console = true`.
+pub const EMBEDDED_CONSOLE_SHIM: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/launcher-console.exe"));
+
+/// Marks a shim that carries its entry point name; the launcher reads it back from its own exe.
+pub const ENTRY_TRAILER_MAGIC: &[u8; 8] = b"UVENTRY1";
+
+/// Shim bytes for an entry point: the windowed or console variant, with the entry name
+/// appended as `<name><u32 LE length><magic>` for named entry points.
+pub fn shim_bytes(console: bool, entry_name: Option<&str>) -> Result<Vec<u8>> {
+    let base = if console {
+        EMBEDDED_CONSOLE_SHIM
+    } else {
+        EMBEDDED_SHIM
+    };
+    if base.is_empty() {
+        bail!(
+            "embedded {} shim is empty",
+            if console { "console" } else { "windowed" }
+        );
+    }
+    let mut bytes = base.to_vec();
+    if let Some(name) = entry_name {
+        append_entry_trailer(&mut bytes, name);
+    }
+    Ok(bytes)
+}
+
+fn append_entry_trailer(bytes: &mut Vec<u8>, name: &str) {
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(ENTRY_TRAILER_MAGIC);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_trailer_ends_with_name_and_magic() {
+        let mut bytes = b"MZ-shim".to_vec();
+        append_entry_trailer(&mut bytes, "cli");
+        assert!(bytes.starts_with(b"MZ-shim"));
+        assert!(bytes.ends_with(ENTRY_TRAILER_MAGIC));
+        let len_at = bytes.len() - ENTRY_TRAILER_MAGIC.len() - 4;
+        assert_eq!(&bytes[len_at..len_at + 4], &3u32.to_le_bytes());
+        assert_eq!(&bytes[len_at - 3..len_at], b"cli");
+    }
+}
@@ -1,11 +1,13 @@
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
     fs,
     path::{Path, PathBuf},
-    time::{Duration, UNIX_EPOCH},
 };
 
+use crate::entry_spec;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "kind", content = "value")]
 pub enum EntryPoint {
@@ -17,95 +19,279 @@ pub enum EntryPoint {
     Command(String),
 }
 
+/// Name of the project from `project` in config.toml; it runs the default entry point.
+pub const MAIN_PROJECT: &str = "main";
+
+/// An additional entry point from `[entry_points.<name>]`, launched by the `<name>.exe` shim.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamedEntry {
+    pub name: String,
+    pub entry: EntryPoint,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "main_project")]
+    pub project: String,
+}
+
+/// A uv project under `app/`, synced into its own venv (see `venv_dir`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Project {
+    pub name: String,
+    pub rel: String,
+    #[serde(default)]
+    pub fingerprint: Fingerprint,
+}
+
+/// What a project's venv was synced from; the launcher syncs again when any of it differs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// sha256 of `uv.lock`, `pyproject.toml` and `.python-version`; empty when absent.
+    pub uv_lock: String,
+    pub pyproject: String,
+    pub python_version: String,
+    /// Interpreter version from the venv's pyvenv.cfg.
+    pub interpreter: String,
+}
+
+impl Fingerprint {
+    pub fn read(proj: &Path, venv: &Path) -> Result<Self> {
+        Ok(Self {
+            uv_lock: file_sha256(&proj.join("uv.lock"))?,
+            pyproject: file_sha256(&proj.join("pyproject.toml"))?,
+            python_version: file_sha256(&proj.join(".python-version"))?,
+            interpreter: venv_interpreter(venv)?,
+        })
+    }
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    Ok(Sha256::digest(&bytes).iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// `version_info` (written by uv) or `version` (written by the venv module) from pyvenv.cfg.
+fn venv_interpreter(venv: &Path) -> Result<String> {
+    let cfg = venv.join("pyvenv.cfg");
+    if !cfg.exists() {
+        return Ok(String::new());
+    }
+    let contents = fs::read_to_string(&cfg).with_context(|| format!("read {}", cfg.display()))?;
+    let mut version = String::new();
+    for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+        match key.trim() {
+            "version_info" => return Ok(value.trim().to_string()),
+            "version" => version = value.trim().to_string(),
+            _ => {}
+        }
+    }
+    Ok(version)
+}
+
+/// A project resolved to its directory in the install root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectDir {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "StateFile")]
 pub struct State {
-    pub project_rel: String,
+    pub projects: Vec<Project>,
     pub entry: EntryPoint,
-    pub lock_mtime_unix: u64,
     pub installed: bool,
     #[serde(default)]
     pub launcher_version: String,
+    /// Static arguments from config.toml, passed before any forwarded from the command line.
+    #[serde(default)]
+    pub entry_args: Vec<String>,
+    #[serde(default)]
+    pub entry_points: Vec<NamedEntry>,
+    /// Start menu shortcuts this install created, so an upgrade or uninstall also removes
+    /// the ones a later config dropped.
+    #[serde(default)]
+    pub shortcuts: Vec<String>,
+}
+
+/// On-disk form of `State`, which also accepts the single-project `project_rel` layout.
+#[derive(Deserialize)]
+struct StateFile {
+    #[serde(default)]
+    projects: Vec<Project>,
+    #[serde(default)]
+    project_rel: Option<String>,
+    entry: EntryPoint,
+    installed: bool,
+    #[serde(default)]
+    launcher_version: String,
+    #[serde(default)]
+    entry_args: Vec<String>,
+    #[serde(default)]
+    entry_points: Vec<NamedEntry>,
+    #[serde(default)]
+    shortcuts: Vec<String>,
+}
+
+impl From<StateFile> for State {
+    fn from(file: StateFile) -> Self {
+        let mut projects = file.projects;
+        if let (true, Some(rel)) = (projects.is_empty(), file.project_rel) {
+            projects.push(Project {
+                name: MAIN_PROJECT.to_string(),
+                rel,
+                fingerprint: Fingerprint::default(),
+            });
+        }
+        State {
+            projects,
+            entry: file.entry,
+            installed: file.installed,
+            launcher_version: file.launcher_version,
+            entry_args: file.entry_args,
+            entry_points: file.entry_points,
+            shortcuts: file.shortcuts,
+        }
+    }
+}
+
+fn main_project() -> String {
+    MAIN_PROJECT.to_string()
 }
 
 pub fn state_path(root: &Path) -> PathBuf {
     root.join(".runtime").join("state.json")
 }
 
+/// The venv a project syncs into: `.runtime/venv` for the main project (the layout from
+/// before multi-project support), `.runtime/venv-<name>` for the others.
+pub fn venv_dir(runtime: &Path, project: &str) -> PathBuf {
+    if project == MAIN_PROJECT {
+        runtime.join("venv")
+    } else {
+        runtime.join(format!("venv-{project}"))
+    }
+}
+
 #[cfg(test)]
 pub fn state_exists(root: &Path) -> bool {
     state_path(root).exists()
 }
 
-pub fn file_mtime_unix(path: &Path) -> Result<u64> {
-    let meta = fs::metadata(path).with_context(|| format!("metadata {}", path.display()))?;
-    let mtime = meta.modified().context("modified time")?;
-    Ok(mtime
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs())
-}
-
-pub fn default_state_for_project(root: &Path, proj: &Path) -> Result<State> {
-    let rel = proj.strip_prefix(root).unwrap_or(proj);
-    let rel_str = rel.to_string_lossy().to_string();
+pub fn default_state_for_projects(root: &Path, projects: &[ProjectDir]) -> Result<State> {
+    let main = projects
+        .iter()
+        .find(|p| p.name == MAIN_PROJECT)
+        .context("main project missing")?;
+    let proj = main.dir.as_path();
 
-    let entry = if let Some(entry) = entry_point_from_config(proj)? {
-        entry
+    let (entry, mut entry_args) = if let Some(parsed) = entry_point_from_config(proj)? {
+        parsed
     } else {
         let main_py = proj.join("main.py");
         if !main_py.exists() {
             bail!("Default entrypoint main.py not found at {}", main_py.display());
         }
-        EntryPoint::PythonFile("main.py".to_string())
+        (EntryPoint::PythonFile("main.py".to_string()), Vec::new())
     };
+    entry_args.extend(crate::config::ENTRY_ARGS.iter().map(|a| a.to_string()));
 
     Ok(State {
-        project_rel: rel_str,
+        projects: projects
+            .iter()
+            .map(|p| Project {
+                name: p.name.clone(),
+                rel: p
+                    .dir
+                    .strip_prefix(root)
+                    .unwrap_or(&p.dir)
+                    .to_string_lossy()
+                    .to_string(),
+                fingerprint: Fingerprint::default(),
+            })
+            .collect(),
         entry,
-        lock_mtime_unix: 0,
         installed: true,
         launcher_version: crate::config::VERSION.to_string(),
+        entry_args,
+        entry_points: named_entries_from_config(projects)?,
+        shortcuts: Vec::new(),
     })
 }
 
-fn entry_point_from_config(proj: &Path) -> Result<Option<EntryPoint>> {
+fn entry_point_from_config(proj: &Path) -> Result<Option<(EntryPoint, Vec<String>)>> {
     let raw = crate::config::ENTRY_POINT.trim();
     if raw.is_empty() {
         return Ok(None);
     }
-    let entry = parse_entry_point(raw)?;
+    resolve_entry_point(proj, raw, "entry_point").map(Some)
+}
+
+fn named_entries_from_config(projects: &[ProjectDir]) -> Result<Vec<NamedEntry>> {
+    crate::config::ENTRY_POINTS
+        .iter()
+        .map(|named| {
+            let project = if named.project.is_empty() {
+                MAIN_PROJECT
+            } else {
+                named.project
+            };
+            let proj = projects.iter().find(|p| p.name == project).with_context(|| {
+                format!("entry point {} uses unknown project {project}", named.name)
+            })?;
+            let field = format!("entry_points.{}.target", named.name);
+            let (entry, mut args) = resolve_entry_point(&proj.dir, named.target, &field)?;
+            args.extend(named.args.iter().map(|a| a.to_string()));
+            Ok(NamedEntry {
+                name: named.name.to_string(),
+                entry,
+                args,
+                project: project.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn resolve_entry_point(proj: &Path, raw: &str, field: &str) -> Result<(EntryPoint, Vec<String>)> {
+    let (entry, args) =
+        parse_entry_point(raw).with_context(|| format!("invalid config.toml {field}"))?;
     if let EntryPoint::PythonFile(ref f) = entry {
         let path = proj.join(f);
         if !path.exists() {
             bail!(
-                "Configured entry_point not found: {} (config.toml entry_point)",
+                "Configured entry_point not found: {} (config.toml {field})",
                 path.display()
             );
         }
     }
-    Ok(Some(entry))
+    Ok((entry, args))
 }
 
-fn parse_entry_point(raw: &str) -> Result<EntryPoint> {
+/// Parses `[module:|command:]<target> [args...]`; arguments may use the placeholders in
+/// `entry_spec::PLACEHOLDERS`, which the launcher expands at run time.
+fn parse_entry_point(raw: &str) -> Result<(EntryPoint, Vec<String>)> {
     let trimmed = raw.trim();
     if let Some(rest) = trimmed.strip_prefix("module:") {
-        let val = rest.trim();
-        if val.is_empty() {
+        if rest.trim().is_empty() {
             bail!("entry_point module is empty");
         }
-        return Ok(EntryPoint::Module(val.to_string()));
+        let (module, args) = entry_spec::split(rest).map_err(anyhow::Error::msg)?;
+        return Ok((EntryPoint::Module(module), args));
     }
     if let Some(rest) = trimmed.strip_prefix("command:") {
-        let val = rest.trim();
-        if val.is_empty() {
+        if rest.trim().is_empty() {
             bail!("entry_point command is empty");
         }
-        return Ok(EntryPoint::Command(val.to_string()));
+        let (command, args) = entry_spec::split(rest).map_err(anyhow::Error::msg)?;
+        return Ok((EntryPoint::Command(command), args));
     }
     if trimmed.is_empty() {
         bail!("entry_point is empty");
     }
-    Ok(EntryPoint::PythonFile(trimmed.to_string()))
+    let (file, args) = entry_spec::split(trimmed).map_err(anyhow::Error::msg)?;
+    Ok((EntryPoint::PythonFile(file), args))
 }
 
 pub fn read_state(state_path: &Path) -> Result<State> {
@@ -123,11 +309,11 @@ pub fn write_state(state_path: &Path, state: &State) -> Result<()> {
 }
 
 #[cfg(test)]
-pub fn read_or_init_state(state_path: &Path, root: &Path, proj: &Path) -> Result<State> {
+pub fn read_or_init_state(state_path: &Path, root: &Path, projects: &[ProjectDir]) -> Result<State> {
     if state_path.exists() {
         return read_state(state_path);
     }
-    let st = default_state_for_project(root, proj)?;
+    let st = default_state_for_projects(root, projects)?;
     write_state(state_path, &st)?;
     Ok(st)
 }
@@ -139,11 +325,26 @@ mod tests {
     #[test]
     fn serde_roundtrip_state() {
         let state = State {
-            project_rel: "app\\proj".to_string(),
+            projects: vec![Project {
+                name: MAIN_PROJECT.to_string(),
+                rel: "app\\proj".to_string(),
+                fingerprint: Fingerprint {
+                    uv_lock: "ab12".to_string(),
+                    interpreter: "3.12.4".to_string(),
+                    ..Fingerprint::default()
+                },
+            }],
             entry: EntryPoint::PythonFile("main.py".to_string()),
-            lock_mtime_unix: 123,
             installed: true,
             launcher_version: "1.2.3".to_string(),
+            entry_args: vec!["--verbose".to_string()],
+            entry_points: vec![NamedEntry {
+                name: "cli".to_string(),
+                entry: EntryPoint::Module("app.cli".to_string()),
+                args: Vec::new(),
+                project: "sidecar".to_string(),
+            }],
+            shortcuts: vec!["My App".to_string(), "My App cli".to_string()],
         };
         let s = serde_json::to_string(&state).unwrap();
         let out: State = serde_json::from_str(&s).unwrap();
@@ -159,31 +360,80 @@ mod tests {
         fs::write(proj.join("main.py"), "print('hi')").unwrap();
 
         let st_path = state_path(root);
-        let state = read_or_init_state(&st_path, root, &proj).unwrap();
+        let projects = vec![ProjectDir {
+            name: MAIN_PROJECT.to_string(),
+            dir: proj.clone(),
+        }];
+        let state = read_or_init_state(&st_path, root, &projects).unwrap();
 
         assert!(st_path.exists());
-        assert_eq!(state.project_rel, PathBuf::from("app").join("proj").to_string_lossy());
         assert_eq!(
             state,
             State {
-                project_rel: PathBuf::from("app").join("proj").to_string_lossy().to_string(),
+                projects: vec![Project {
+                    name: MAIN_PROJECT.to_string(),
+                    rel: PathBuf::from("app").join("proj").to_string_lossy().to_string(),
+                    fingerprint: Fingerprint::default(),
+                }],
                 entry: EntryPoint::PythonFile("main.py".to_string()),
-                lock_mtime_unix: 0,
                 installed: true,
                 launcher_version: crate::config::VERSION.to_string(),
+                entry_args: entry_point_from_config(&proj)
+                    .unwrap()
+                    .map(|(_, args)| args)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(crate::config::ENTRY_ARGS.iter().map(|a| a.to_string()))
+                    .collect(),
+                entry_points: named_entries_from_config(&projects).unwrap(),
+                shortcuts: Vec::new(),
             }
         );
     }
 
+    #[test]
+    fn legacy_single_project_state_migrates() {
+        let legacy = r#"{
+            "project_rel": "app\\proj",
+            "entry": {"kind": "python_file", "value": "main.py"},
+            "lock_mtime_unix": 42,
+            "installed": true,
+            "launcher_version": "1.0.0"
+        }"#;
+        let state: State = serde_json::from_str(legacy).unwrap();
+        assert_eq!(
+            state.projects,
+            vec![Project {
+                name: MAIN_PROJECT.to_string(),
+                rel: "app\\proj".to_string(),
+                fingerprint: Fingerprint::default(),
+            }]
+        );
+        let runtime = Path::new(".runtime");
+        assert_eq!(venv_dir(runtime, MAIN_PROJECT), runtime.join("venv"));
+        assert_eq!(venv_dir(runtime, "sidecar"), runtime.join("venv-sidecar"));
+    }
+
     #[test]
     fn parse_entry_point_variants() {
-        let py = parse_entry_point("main.py").unwrap();
+        let (py, _) = parse_entry_point("main.py").unwrap();
         assert_eq!(py, EntryPoint::PythonFile("main.py".to_string()));
 
-        let module = parse_entry_point("module:pkg.__main__").unwrap();
+        let (module, _) = parse_entry_point("module:pkg.__main__").unwrap();
         assert_eq!(module, EntryPoint::Module("pkg.__main__".to_string()));
 
-        let cmd = parse_entry_point("command:mycli").unwrap();
+        let (cmd, args) = parse_entry_point("command:mycli").unwrap();
         assert_eq!(cmd, EntryPoint::Command("mycli".to_string()));
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn parse_entry_point_splits_arguments() {
+        let (cmd, args) =
+            parse_entry_point("command:myapp --config \"{data_dir}/app config.toml\"").unwrap();
+        assert_eq!(cmd, EntryPoint::Command("myapp".to_string()));
+        assert_eq!(args, ["--config", "{data_dir}/app config.toml"]);
+
+        assert!(parse_entry_point("module:pkg --home {home}").is_err());
     }
 }
@@ -1,29 +1,58 @@
 #[path = "../src/installer.rs"]
 mod installer;
-#[path = "../src/payload.rs"]
-mod payload;
+#[path = "../src/cancel.rs"]
+mod cancel;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../../builder-rust/src/declared_scripts.rs"]
+mod declared_scripts;
+#[path = "../../launcher-rust/src/diagnostics.rs"]
+mod diagnostics;
+#[path = "../src/entry_spec.rs"]
+mod entry_spec;
+#[path = "../../launcher-rust/src/env_lock.rs"]
+mod env_lock;
+#[path = "../../launcher-rust/src/env_policy.rs"]
+mod env_policy;
 #[path = "../src/fs_ops.rs"]
 mod fs_ops;
-#[path = "../src/state.rs"]
-mod state;
-#[path = "../src/shortcuts.rs"]
-mod shortcuts;
+#[path = "../src/install_log.rs"]
+mod install_log;
+#[cfg_attr(not(windows), allow(dead_code))]
+#[path = "../../launcher-rust/src/instance_name.rs"]
+mod instance_name;
 #[path = "../src/paths.rs"]
 mod paths;
+#[path = "../src/payload.rs"]
+mod payload;
+#[path = "../src/preflight.rs"]
+mod preflight;
+#[path = "../src/processes.rs"]
+mod processes;
+#[path = "../src/progress.rs"]
+mod progress;
+#[path = "../src/retry.rs"]
+mod retry;
+#[path = "../src/shim_payload.rs"]
+mod shim_payload;
+#[path = "../src/shortcuts.rs"]
+mod shortcuts;
+#[path = "../src/state.rs"]
+mod state;
+#[path = "../../launcher-rust/src/timestamp.rs"]
+mod timestamp;
+#[path = "../src/ui_payload.rs"]
+mod ui_payload;
 #[path = "../src/uv.rs"]
 mod uv;
 
 use std::fs;
+use std::time::Duration;
 
 #[test]
 fn installer_writes_state_and_runs_uv_commands() {
     let tmp = tempfile::tempdir().unwrap();
-    let src_root = tmp.path().join("src");
     let install_root = tmp.path().join("install");
-    fs::create_dir_all(&src_root).unwrap();
-
-    let self_exe = src_root.join("MyApp.exe");
-    fs::write(&self_exe, "binary").unwrap();
 
     let ensure_uv = |root: &std::path::Path| -> anyhow::Result<()> {
         fs::write(root.join("uv.exe"), "fake")?;
@@ -31,14 +60,19 @@ fn installer_writes_state_and_runs_uv_commands() {
     };
 
     let mut seen = Vec::new();
-    let exec = |cmd: &mut std::process::Command| -> anyhow::Result<std::process::ExitStatus> {
+    let exec = |cmd: &mut std::process::Command,
+                _timeout: Duration|
+     -> anyhow::Result<retry::CommandOutput> {
         let program = cmd.get_program().to_string_lossy().to_string();
         let args: Vec<String> = cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect();
         seen.push((program, args));
         #[cfg(windows)]
         {
             use std::os::windows::process::ExitStatusExt;
-            Ok(std::process::ExitStatus::from_raw(0))
+            Ok(retry::CommandOutput {
+                status: Some(std::process::ExitStatus::from_raw(0)),
+                stderr: String::new(),
+            })
         }
         #[cfg(not(windows))]
         {
@@ -55,25 +89,36 @@ fn installer_writes_state_and_runs_uv_commands() {
     };
 
     let launch = |_exe: &std::path::Path| -> anyhow::Result<()> { Ok(()) };
+    let close_app = |_root: &std::path::Path| -> anyhow::Result<()> { Ok(()) };
 
+    let log = install_log::InstallLog::open("MyApp");
+    let cancel = cancel::CancelToken::new(None);
+    let progress = progress::ProgressReporter::new(None);
     installer::run_with_deps(
-        &src_root,
         &install_root,
         "MyApp",
-        &self_exe,
         ensure_uv,
         exec,
         create_shortcut,
         launch,
+        close_app,
+        &installer::InstallContext {
+            log: &log,
+            preflight_path: None,
+            cancel: &cancel,
+            progress: &progress,
+        },
     )
     .unwrap();
+    log.finish();
 
     let state_path = state::state_path(&install_root);
     assert!(state_path.exists());
 
     let st = state::read_state(&state_path).unwrap();
     let expected_proj = find_project_rel(&install_root).unwrap();
-    assert_eq!(st.project_rel, expected_proj);
+    assert_eq!(st.projects[0].name, state::MAIN_PROJECT);
+    assert_eq!(st.projects[0].rel, expected_proj);
     assert!(install_root.join("MyApp.exe").exists());
 
     assert_eq!(seen.len(), 2);
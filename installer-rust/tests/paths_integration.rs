@@ -1,3 +1,5 @@
+#[path = "../src/config.rs"]
+mod config;
 #[path = "../src/paths.rs"]
 mod paths;
 
@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use std::{
+    collections::BTreeMap,
     fs,
     io,
     path::{Path, PathBuf},
@@ -15,6 +16,95 @@ struct Config {
     version: String,
     #[serde(default)]
     icon: String,
+    #[serde(default = "default_single_instance")]
+    single_instance: bool,
+    #[serde(default)]
+    logs: LogsConfig,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    env_policy: EnvPolicyConfig,
+    #[serde(default)]
+    restart: RestartConfig,
+}
+
+fn default_single_instance() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct EnvPolicyConfig {
+    isolate: bool,
+    allow: Vec<String>,
+}
+
+impl Default for EnvPolicyConfig {
+    fn default() -> Self {
+        Self {
+            isolate: true,
+            allow: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RestartConfig {
+    policy: RestartPolicy,
+    max_restarts: usize,
+    backoff_ms: u64,
+    max_backoff_ms: u64,
+    crash_loop_restarts: usize,
+    crash_loop_window_secs: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RestartPolicy::Never => "never",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Always => "always",
+        }
+    }
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::Never,
+            max_restarts: 10,
+            backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+            crash_loop_restarts: 5,
+            crash_loop_window_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct LogsConfig {
+    max_size_kb: u64,
+    retention: usize,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: 1024,
+            retention: 5,
+        }
+    }
 }
 
 fn main() {
@@ -96,5 +186,22 @@ fn write_config_rs(out_dir: &Path, config: &Config) -> io::Result<()> {
     writeln!(file, "pub const APP_ID: &str = {:?};", config.app_id)?;
     writeln!(file, "pub const NAME: &str = {:?};", config.name)?;
     writeln!(file, "pub const PRODUCT_NAME: &str = {:?};", config.product_name)?;
+    writeln!(file, "pub const SINGLE_INSTANCE: bool = {};", config.single_instance)?;
+    writeln!(file, "pub const LOG_MAX_BYTES: u64 = {};", config.logs.max_size_kb * 1024)?;
+    writeln!(file, "pub const LOG_RETENTION: usize = {};", config.logs.retention)?;
+    writeln!(file, "pub const ENV_VARS: &[(&str, &str)] = &[")?;
+    for (key, value) in &config.env {
+        writeln!(file, "    ({:?}, {:?}),", key, value)?;
+    }
+    writeln!(file, "];")?;
+    writeln!(file, "pub const ENV_ISOLATE: bool = {};", config.env_policy.isolate)?;
+    writeln!(file, "pub const ENV_ALLOW: &[&str] = &{:?};", config.env_policy.allow)?;
+    let restart = &config.restart;
+    writeln!(file, "pub const RESTART_POLICY: &str = {:?};", restart.policy.as_str())?;
+    writeln!(file, "pub const RESTART_MAX: usize = {};", restart.max_restarts)?;
+    writeln!(file, "pub const RESTART_BACKOFF_MS: u64 = {};", restart.backoff_ms)?;
+    writeln!(file, "pub const RESTART_MAX_BACKOFF_MS: u64 = {};", restart.max_backoff_ms)?;
+    writeln!(file, "pub const CRASH_LOOP_RESTARTS: usize = {};", restart.crash_loop_restarts)?;
+    writeln!(file, "pub const CRASH_LOOP_WINDOW_SECS: u64 = {};", restart.crash_loop_window_secs)?;
     Ok(())
 }
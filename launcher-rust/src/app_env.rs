@@ -0,0 +1,51 @@
+//! The `UVESSEL_*` variables exported to the app, documented in README.md.
+
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the variable set, exported as `UVESSEL_ENV_VERSION`. Adding a variable keeps
+/// the version; renaming, removing or changing the meaning of one bumps it.
+pub const ENV_VERSION: u32 = 1;
+
+/// Variables for one launch; `launch_id` also tags the launcher log.
+pub fn vars(root: &Path, app_id: &str, version: &str, launch_id: &str) -> Vec<(String, String)> {
+    let path = |p: &Path| p.to_string_lossy().to_string();
+    vec![
+        ("UVESSEL_ENV_VERSION".to_string(), ENV_VERSION.to_string()),
+        ("UVESSEL_ROOT".to_string(), path(root)),
+        ("UVESSEL_DATA_DIR".to_string(), path(&root.join("data"))),
+        ("UVESSEL_ASSETS_DIR".to_string(), path(&root.join("assets"))),
+        ("UVESSEL_APP_ID".to_string(), app_id.to_string()),
+        ("UVESSEL_VERSION".to_string(), version.to_string()),
+        ("UVESSEL_LOG_DIR".to_string(), path(&crate::logging::logs_dir(root))),
+        ("UVESSEL_LAUNCH_ID".to_string(), launch_id.to_string()),
+    ]
+}
+
+/// Unique per launch: start time in milliseconds and the launcher's pid, both in hex.
+pub fn new_launch_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!("{millis:x}-{:x}", std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vars_cover_the_documented_contract() {
+        let root = Path::new("install");
+        let vars = vars(root, "com.example.app", "1.2.3", "abc-1");
+        let get = |key: &str| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("UVESSEL_ENV_VERSION"), Some("1"));
+        assert_eq!(get("UVESSEL_DATA_DIR"), Some(root.join("data").to_str().unwrap()));
+        assert_eq!(get("UVESSEL_APP_ID"), Some("com.example.app"));
+        assert_eq!(get("UVESSEL_LAUNCH_ID"), Some("abc-1"));
+        assert_eq!(vars.len(), 8);
+    }
+}
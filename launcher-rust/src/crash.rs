@@ -0,0 +1,190 @@
+//! Crash capture: when the app exits non-zero, a record of the exit goes to
+//! `.runtime/crashes` and the installer UI, which the installer copies to `.runtime`, shows
+//! it to the user in its "error" mode.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::logging::{self, Logger};
+use crate::timestamp;
+
+/// The installer UI binary inside `.runtime`; the installer writes it under the same name.
+pub const UI_EXE: &str = "uvessel-ui.exe";
+
+/// Crash records kept; older ones are deleted when a new one is written.
+const MAX_RECORDS: usize = 20;
+
+/// What is known about one failed run of the app.
+pub struct Crash<'a> {
+    pub exit_code: i32,
+    pub entry_point: Option<&'a str>,
+    pub launch_id: &'a str,
+    pub app_version: &'a str,
+    pub python: Option<&'a str>,
+    pub stderr_tail: Vec<String>,
+}
+
+pub fn crashes_dir(root: &Path) -> PathBuf {
+    root.join(".runtime").join("crashes")
+}
+
+/// Records the crash and, when `show_window` is set, opens the error window; failures are
+/// logged because the app's exit code is returned either way.
+pub fn report(root: &Path, log: &Logger, crash: &Crash, show_window: bool) {
+    let record = match write_record(root, crash) {
+        Ok(record) => record,
+        Err(err) => {
+            log.error(&format!("could not write a crash record: {err:#}"));
+            return;
+        }
+    };
+    log.info(&format!("crash record written to {}", record.display()));
+    if show_window {
+        if let Err(err) = open_error_window(root, &record) {
+            log.error(&format!("could not show the error window: {err:#}"));
+        }
+    }
+}
+
+fn write_record(root: &Path, crash: &Crash) -> Result<PathBuf> {
+    let dir = crashes_dir(root);
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = dir.join(format!("crash-{millis}.json"));
+    let record = json!({
+        "timestamp": timestamp::now(),
+        "app_name": app_name(),
+        "exit_code": crash.exit_code,
+        "entry_point": crash.entry_point,
+        "launch_id": crash.launch_id,
+        "traceback": final_traceback(&crash.stderr_tail),
+        "stderr_tail": crash.stderr_tail,
+        "versions": {
+            "app": crash.app_version,
+            "launcher": env!("CARGO_PKG_VERSION"),
+            "python": crash.python,
+        },
+        "log_dir": logging::logs_dir(root),
+    });
+    fs::write(&path, serde_json::to_string_pretty(&record)?)
+        .with_context(|| format!("write {}", path.display()))?;
+    prune(&dir, MAX_RECORDS);
+    Ok(path)
+}
+
+/// The last Python traceback or `faulthandler` dump in the stderr tail.
+fn final_traceback(lines: &[String]) -> Option<String> {
+    let start = lines.iter().rposition(|line| {
+        line.starts_with("Traceback (most recent call last)")
+            || line.starts_with("Fatal Python error")
+    })?;
+    Some(lines[start..].join("\n"))
+}
+
+/// Deletes all but the newest `keep` records; their names sort by creation time.
+fn prune(dir: &Path, keep: usize) {
+    let mut records: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("crash-") && name.ends_with(".json")
+        })
+        .collect();
+    records.sort();
+    let excess = records.len().saturating_sub(keep);
+    for old in &records[..excess] {
+        let _ = fs::remove_file(old);
+    }
+}
+
+fn open_error_window(root: &Path, record: &Path) -> Result<()> {
+    let ui = root.join(".runtime").join(UI_EXE);
+    if !ui.exists() {
+        return Ok(());
+    }
+    let mut cmd = Command::new(&ui);
+    cmd.arg("--mode")
+        .arg("error")
+        .arg("--name")
+        .arg(app_name())
+        .arg("--error-file")
+        .arg(record)
+        .arg("--log-dir")
+        .arg(logging::logs_dir(root))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Left running on its own; the launcher exits with the app's code right away.
+    cmd.spawn()
+        .with_context(|| format!("spawn {}", ui.display()))?;
+    Ok(())
+}
+
+fn app_name() -> &'static str {
+    if !crate::config::PRODUCT_NAME.trim().is_empty() {
+        crate::config::PRODUCT_NAME
+    } else {
+        crate::config::NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn final_traceback_starts_at_the_last_one() {
+        let tail = lines(
+            "Traceback (most recent call last):\n  old\nValueError: handled\nstarting\n\
+             Traceback (most recent call last):\n  File \"main.py\", line 3\nKeyError: 'x'",
+        );
+        assert_eq!(
+            final_traceback(&tail).as_deref(),
+            Some("Traceback (most recent call last):\n  File \"main.py\", line 3\nKeyError: 'x'")
+        );
+        assert_eq!(final_traceback(&lines("just output")), None);
+    }
+
+    #[test]
+    fn record_holds_the_crash_and_old_records_are_pruned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = crashes_dir(tmp.path());
+        fs::create_dir_all(&dir).unwrap();
+        for i in 0..MAX_RECORDS {
+            fs::write(dir.join(format!("crash-{:03}.json", i)), "{}").unwrap();
+        }
+        let crash = Crash {
+            exit_code: 1,
+            entry_point: Some("cli"),
+            launch_id: "abc-1",
+            app_version: "1.2.3",
+            python: Some("3.12.4"),
+            stderr_tail: lines("Traceback (most recent call last):\nSystemExit: 1"),
+        };
+
+        let path = write_record(tmp.path(), &crash).unwrap();
+        let record: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(record["exit_code"], 1);
+        assert_eq!(record["versions"]["python"], "3.12.4");
+        assert_eq!(record["traceback"], "Traceback (most recent call last):\nSystemExit: 1");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), MAX_RECORDS);
+        assert!(!dir.join("crash-000.json").exists());
+    }
+}
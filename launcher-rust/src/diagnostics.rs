@@ -0,0 +1,331 @@
+//! Support bundle: one zip holding what a bug report needs from an install. The launcher
+//! writes it for `--uvessel-diagnose`, the installer from its UI after a failed install;
+//! the installer includes this file with `#[path]`.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// sha256 of every payload file under `app/`, by path relative to the install root; written
+/// by the installer into `.runtime`.
+pub const MANIFEST_FILE: &str = "payload-manifest.json";
+
+/// Variable names containing one of these have their values left out of the bundle.
+const SECRET_MARKERS: &[&str] = &[
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "CREDENTIAL",
+    "AUTH",
+    "COOKIE",
+    "SESSION",
+];
+
+pub fn manifest_path(root: &Path) -> PathBuf {
+    root.join(".runtime").join(MANIFEST_FILE)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Writes the bundle into `.runtime/logs` (the temp dir when that cannot be created) and
+/// returns its path. `created_by` names the caller; `extra_logs` are added to the logs
+/// found in the install, e.g. an installer log that had to fall back to the temp dir.
+pub fn write_bundle(root: &Path, created_by: &str, extra_logs: &[PathBuf]) -> Result<PathBuf> {
+    let runtime = root.join(".runtime");
+    let logs_dir = runtime.join("logs");
+    let out_dir = if fs::create_dir_all(&logs_dir).is_ok() {
+        logs_dir.clone()
+    } else {
+        std::env::temp_dir()
+    };
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let out = out_dir.join(format!("diagnostics-{stamp}.zip"));
+    let file = fs::File::create(&out).with_context(|| format!("create {}", out.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<()> {
+        zip.start_file(name, zip::write::FileOptions::default())
+            .with_context(|| format!("add {name} to {}", out.display()))?;
+        zip.write_all(bytes)?;
+        Ok(())
+    };
+
+    if let Ok(state) = fs::read(runtime.join("state.json")) {
+        add("state.json", &state)?;
+    }
+    for log in collect_logs(&logs_dir, extra_logs) {
+        if let (Some(name), Ok(bytes)) = (log.file_name(), fs::read(&log)) {
+            add(&format!("logs/{}", name.to_string_lossy()), &bytes)?;
+        }
+    }
+
+    let uv = root.join("uv.exe");
+    let uv_version = command_output(uv_cmd(&uv, &runtime).arg("--version"));
+    add("uv-version.txt", uv_version.as_bytes())?;
+    let mut venvs = Vec::new();
+    for venv in venv_dirs(&runtime) {
+        let name = venv.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let python = venv
+            .join(if cfg!(windows) { "Scripts" } else { "bin" })
+            .join(format!("python{}", std::env::consts::EXE_SUFFIX));
+        let version = command_output(Command::new(&python).arg("--version"));
+        let mut pip_list = uv_cmd(&uv, &runtime);
+        pip_list.args(["pip", "list", "--python"]).arg(&python);
+        let packages = command_output(&mut pip_list);
+        add(&format!("venvs/{name}/python-version.txt"), version.as_bytes())?;
+        add(&format!("venvs/{name}/pip-list.txt"), packages.as_bytes())?;
+        venvs.push(name);
+    }
+
+    let payload = verify_payload(root);
+    add("payload-verification.json", serde_json::to_string_pretty(&payload)?.as_bytes())?;
+    add("environment.txt", redacted_env(std::env::vars_os()).as_bytes())?;
+
+    let summary = json!({
+        "created_by": created_by,
+        "created_at_unix": stamp,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "root": root,
+        "venvs": venvs,
+        "disk": {
+            "install_free_bytes": existing_ancestor(root).and_then(|dir| free_space_bytes(&dir)),
+            "temp_free_bytes": free_space_bytes(&std::env::temp_dir()),
+        },
+    });
+    add("summary.json", serde_json::to_string_pretty(&summary)?.as_bytes())?;
+    zip.finish().with_context(|| format!("finish {}", out.display()))?;
+    Ok(out)
+}
+
+/// Launcher and installer logs; the app's own output and earlier bundles stay out.
+fn collect_logs(logs_dir: &Path, extra_logs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut logs: Vec<PathBuf> = fs::read_dir(logs_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.is_file() && !name.starts_with("app.log") && !name.ends_with(".zip")
+        })
+        .collect();
+    logs.sort();
+    for extra in extra_logs {
+        if extra.parent() != Some(logs_dir) {
+            logs.push(extra.clone());
+        }
+    }
+    logs
+}
+
+fn venv_dirs(runtime: &Path) -> Vec<PathBuf> {
+    let mut venvs: Vec<PathBuf> = fs::read_dir(runtime)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.is_dir() && (name == "venv" || name.starts_with("venv-"))
+        })
+        .collect();
+    venvs.sort();
+    venvs
+}
+
+fn uv_cmd(uv: &Path, runtime: &Path) -> Command {
+    let mut cmd = Command::new(uv);
+    cmd.env("UV_NO_CONFIG", "1")
+        .env("UV_CACHE_DIR", runtime.join("cache"))
+        .env("UV_PYTHON_INSTALL_DIR", runtime.join("python"));
+    cmd
+}
+
+/// Exit status and output of `cmd`, or why it could not run.
+fn command_output(cmd: &mut Command) -> String {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    match cmd.output() {
+        Ok(out) => format!(
+            "{}\n{}{}",
+            out.status,
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        ),
+        Err(err) => format!("failed to run {}: {err}\n", cmd.get_program().to_string_lossy()),
+    }
+}
+
+/// Compares the installed `app/` files with the manifest the installer recorded.
+fn verify_payload(root: &Path) -> Value {
+    let Ok(text) = fs::read_to_string(manifest_path(root)) else {
+        return json!({ "manifest": "missing" });
+    };
+    let Ok(manifest) = serde_json::from_str::<BTreeMap<String, String>>(&text) else {
+        return json!({ "manifest": "unreadable" });
+    };
+    let mut missing = Vec::new();
+    let mut modified = Vec::new();
+    for (rel, expected) in &manifest {
+        match fs::read(root.join(rel)) {
+            Ok(bytes) if sha256_hex(&bytes) == *expected => {}
+            Ok(_) => modified.push(rel),
+            Err(_) => missing.push(rel),
+        }
+    }
+    json!({
+        "manifest": "present",
+        "files": manifest.len(),
+        "missing": missing,
+        "modified": modified,
+    })
+}
+
+/// `KEY=value` lines, sorted, with secrets and credentials in URLs removed.
+fn redacted_env(vars: impl Iterator<Item = (std::ffi::OsString, std::ffi::OsString)>) -> String {
+    let mut lines: Vec<String> = vars
+        .map(|(key, value)| {
+            let key = key.to_string_lossy().to_string();
+            let upper = key.to_ascii_uppercase();
+            let value = if SECRET_MARKERS.iter().any(|m| upper.contains(m)) {
+                "<redacted>".to_string()
+            } else {
+                strip_url_credentials(&value.to_string_lossy())
+            };
+            format!("{key}={value}\n")
+        })
+        .collect();
+    lines.sort();
+    lines.concat()
+}
+
+/// Replaces the `user:password@` part of every URL in `value`.
+fn strip_url_credentials(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find("://") {
+        let (head, tail) = rest.split_at(pos + 3);
+        out.push_str(head);
+        let authority = tail
+            .find(['/', ' ', ';', ',', '"'])
+            .map_or(tail, |end| &tail[..end]);
+        rest = match authority.rfind('@') {
+            Some(at) => {
+                out.push_str("<redacted>");
+                &tail[at..]
+            }
+            None => tail,
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.exists()).map(Path::to_path_buf)
+}
+
+/// Free bytes on the volume holding `dir`; `None` where that is not known. The installer's
+/// preflight disk check uses it too.
+#[cfg(windows)]
+pub fn free_space_bytes(dir: &Path) -> Option<u64> {
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(once(0)).collect();
+    let mut free: u64 = 0;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut free,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(free)
+}
+
+#[cfg(not(windows))]
+pub fn free_space_bytes(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+    use std::io::Read;
+
+    #[test]
+    fn environment_hides_secrets_and_url_credentials() {
+        let vars = [
+            ("API_TOKEN", "abc"),
+            ("UV_INDEX_URL", "https://me:pw@pypi.example/simple"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(k, v)| (OsString::from(k), OsString::from(v)));
+        assert_eq!(
+            redacted_env(vars.into_iter()),
+            "API_TOKEN=<redacted>\nPATH=/usr/bin\n\
+             UV_INDEX_URL=https://<redacted>@pypi.example/simple\n"
+        );
+    }
+
+    #[test]
+    fn bundle_holds_state_logs_and_payload_check() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let logs = root.join(".runtime").join("logs");
+        fs::create_dir_all(&logs).unwrap();
+        fs::write(root.join(".runtime").join("state.json"), "{}").unwrap();
+        fs::write(logs.join("launcher.log"), "started").unwrap();
+        fs::write(logs.join("app.log"), "private").unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(root.join("app").join("main.py"), "print('changed')").unwrap();
+        let manifest = json!({
+            "app/main.py": sha256_hex(b"print('hi')"),
+            "app/gone.py": sha256_hex(b""),
+        });
+        fs::write(manifest_path(root), manifest.to_string()).unwrap();
+
+        let bundle = write_bundle(root, "launcher", &[]).unwrap();
+        assert_eq!(bundle.parent(), Some(logs.as_path()));
+        let mut zip = zip::ZipArchive::new(fs::File::open(&bundle).unwrap()).unwrap();
+        let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+        assert!(names.contains(&"state.json".to_string()));
+        assert!(names.contains(&"logs/launcher.log".to_string()));
+        assert!(!names.contains(&"logs/app.log".to_string()));
+
+        let mut text = String::new();
+        zip.by_name("payload-verification.json")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        let payload: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(payload["missing"], json!(["app/gone.py"]));
+        assert_eq!(payload["modified"], json!(["app/main.py"]));
+    }
+}
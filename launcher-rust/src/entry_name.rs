@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Written by the installer after the shim image as `<name><u32 LE length><magic>`.
+const TRAILER_MAGIC: &[u8; 8] = b"UVENTRY1";
+const MAX_NAME_LEN: u64 = 256;
+
+/// Entry point name carried by this shim, or `None` for the app's default entry point.
+pub fn current() -> Result<Option<String>> {
+    let exe = std::env::current_exe().context("current_exe")?;
+    read_from(&exe)
+}
+
+pub fn read_from(exe: &Path) -> Result<Option<String>> {
+    let mut file = fs::File::open(exe).with_context(|| format!("open {}", exe.display()))?;
+    let size = file.metadata()?.len();
+    let footer = TRAILER_MAGIC.len() as u64 + 4;
+    if size < footer {
+        return Ok(None);
+    }
+    let mut tail = [0u8; 12];
+    file.seek(SeekFrom::End(-(footer as i64)))?;
+    file.read_exact(&mut tail)?;
+    if &tail[4..] != TRAILER_MAGIC {
+        return Ok(None);
+    }
+    let len = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64;
+    if len == 0 || len > MAX_NAME_LEN || len + footer > size {
+        return Ok(None);
+    }
+    let mut name = vec![0u8; len as usize];
+    file.seek(SeekFrom::End(-((footer + len) as i64)))?;
+    file.read_exact(&mut name)?;
+    Ok(String::from_utf8(name).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_name_from_trailer() {
+        let tmp = tempfile::tempdir().unwrap();
+        let exe = tmp.path().join("cli.exe");
+        let mut bytes = b"MZ-shim".to_vec();
+        bytes.extend_from_slice(b"cli");
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(TRAILER_MAGIC);
+        fs::write(&exe, bytes).unwrap();
+        assert_eq!(read_from(&exe).unwrap().as_deref(), Some("cli"));
+    }
+
+    #[test]
+    fn plain_shim_has_no_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let exe = tmp.path().join("MyApp.exe");
+        fs::write(&exe, b"MZ-plain-shim-without-trailer").unwrap();
+        assert_eq!(read_from(&exe).unwrap(), None);
+    }
+}
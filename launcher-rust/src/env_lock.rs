@@ -0,0 +1,136 @@
+//! Advisory lock taken by the installer, launcher and uninstaller before they change the
+//! venvs under `.runtime` or `state.json`. The installer includes this file with `#[path]`.
+
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::{self, File, TryLockError},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Locked by the holder; the OS releases the lock when the holder exits, however it exits.
+const LOCK_FILE: &str = "env.lock";
+
+/// Who holds the lock, for messages only. Kept apart from the lock file, which Windows does
+/// not let others read while it is locked.
+const HOLDER_FILE: &str = "env.lock.owner";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Held lock; released when dropped.
+#[derive(Debug)]
+pub struct EnvLock {
+    _file: File,
+    holder: PathBuf,
+}
+
+/// The process recorded in the holder file.
+#[derive(Debug, PartialEq, Eq)]
+struct Holder {
+    pid: u32,
+    owner: String,
+}
+
+impl EnvLock {
+    /// Takes the lock in `runtime`, waiting up to `timeout` for another process to release
+    /// it. `on_wait` is called once, with a description of the holder, if the lock is busy.
+    pub fn acquire(
+        runtime: &Path,
+        owner: &str,
+        timeout: Duration,
+        on_wait: impl FnOnce(&str),
+    ) -> Result<Self> {
+        fs::create_dir_all(runtime).with_context(|| format!("create {}", runtime.display()))?;
+        let path = runtime.join(LOCK_FILE);
+        let holder = runtime.join(HOLDER_FILE);
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        let started = Instant::now();
+        let mut on_wait = Some(on_wait);
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    fs::write(&holder, format!("{}\n{owner}\n", std::process::id()))
+                        .with_context(|| format!("write {}", holder.display()))?;
+                    return Ok(Self {
+                        _file: file,
+                        holder,
+                    });
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(err)) => {
+                    return Err(err).with_context(|| format!("lock {}", path.display()));
+                }
+            }
+
+            let who = match read_holder(&holder) {
+                Some(h) => format!("the {} (pid {})", h.owner, h.pid),
+                None => "another process".to_string(),
+            };
+            if started.elapsed() >= timeout {
+                bail!(
+                    "timed out after {}s waiting for {who} to finish changing the environment",
+                    timeout.as_secs()
+                );
+            }
+            if let Some(on_wait) = on_wait.take() {
+                on_wait(&format!("waiting for {who} to finish changing the environment"));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for EnvLock {
+    fn drop(&mut self) {
+        // The lock file stays; only the description of its holder goes.
+        let _ = fs::remove_file(&self.holder);
+    }
+}
+
+fn read_holder(path: &Path) -> Option<Holder> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut lines = text.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let owner = lines.next()?.trim().to_string();
+    Some(Holder { pid, owner })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_lock_times_out_until_released() {
+        let tmp = tempfile::tempdir().unwrap();
+        let runtime = tmp.path().join(".runtime");
+        let lock = EnvLock::acquire(&runtime, "installer", Duration::ZERO, |_| {}).unwrap();
+        assert_eq!(
+            read_holder(&runtime.join(HOLDER_FILE)),
+            Some(Holder {
+                pid: std::process::id(),
+                owner: "installer".to_string()
+            })
+        );
+
+        let err = EnvLock::acquire(&runtime, "launcher", Duration::ZERO, |_| {}).unwrap_err();
+        assert!(format!("{err:#}").contains("the installer (pid"));
+
+        drop(lock);
+        EnvLock::acquire(&runtime, "launcher", Duration::ZERO, |_| {}).unwrap();
+    }
+
+    #[test]
+    fn files_left_by_an_exited_process_do_not_block() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join(LOCK_FILE), "").unwrap();
+        fs::write(tmp.path().join(HOLDER_FILE), format!("{}\nlauncher\n", u32::MAX)).unwrap();
+        let mut waited = false;
+        EnvLock::acquire(tmp.path(), "installer", Duration::ZERO, |_| waited = true).unwrap();
+        assert!(!waited);
+    }
+}
@@ -0,0 +1,97 @@
+//! Which inherited variables the launcher and installer remove before they start uv or the
+//! app. The installer includes this file with `#[path]`.
+
+use std::ffi::OsString;
+
+use crate::config;
+
+/// Inherited variables that point Python or uv at another interpreter, package set or
+/// configuration. `[env_policy]` strips them unless listed in `allow`.
+const CONTAMINATING: &[&str] = &[
+    "PYTHONPATH",
+    "PYTHONHOME",
+    "PYTHONSTARTUP",
+    "PYTHONUSERBASE",
+    "PYTHONEXECUTABLE",
+    "PYTHONPLATLIBDIR",
+    "VIRTUAL_ENV",
+    "CONDA_PREFIX",
+    "CONDA_DEFAULT_ENV",
+    "CONDA_PYTHON_EXE",
+    "PYENV_VERSION",
+];
+const CONTAMINATING_PREFIXES: &[&str] = &["UV_"];
+
+/// uv settings for reaching a package index from a managed network: indexes and their
+/// credentials, TLS and download mirrors. Kept even when isolating; proxies are configured
+/// with HTTP_PROXY, HTTPS_PROXY and NO_PROXY, which are never stripped.
+const UV_NETWORK: &[&str] = &[
+    "UV_DEFAULT_INDEX",
+    "UV_EXTRA_INDEX_URL",
+    "UV_FIND_LINKS",
+    "UV_HTTP_TIMEOUT",
+    "UV_INDEX",
+    "UV_INSECURE_HOST",
+    "UV_KEYRING_PROVIDER",
+    "UV_NATIVE_TLS",
+    "UV_PYPY_INSTALL_MIRROR",
+    "UV_PYTHON_INSTALL_MIRROR",
+];
+/// `UV_INDEX_URL`, `UV_INDEX_STRATEGY` and the `UV_INDEX_<NAME>_USERNAME`/`_PASSWORD` pairs.
+const UV_NETWORK_PREFIXES: &[&str] = &["UV_INDEX_"];
+
+/// Names from the current environment to remove before starting uv or the app.
+pub fn stripped_vars(inherited: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    filter(inherited, config::ENV_ISOLATE, config::ENV_ALLOW)
+}
+
+fn filter(inherited: impl IntoIterator<Item = OsString>, isolate: bool, allow: &[&str]) -> Vec<OsString> {
+    if !isolate {
+        return Vec::new();
+    }
+    inherited
+        .into_iter()
+        .filter(|key| {
+            // Windows variable names are case-insensitive.
+            let Some(upper) = key.to_str().map(str::to_ascii_uppercase) else {
+                return false;
+            };
+            let contaminating = CONTAMINATING.contains(&upper.as_str())
+                || CONTAMINATING_PREFIXES.iter().any(|p| upper.starts_with(p));
+            let network = UV_NETWORK.contains(&upper.as_str())
+                || UV_NETWORK_PREFIXES.iter().any(|p| upper.starts_with(p));
+            contaminating && !network && !allow.iter().any(|a| a.eq_ignore_ascii_case(&upper))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn strips_python_variables_except_allowed() {
+        let inherited = names(&["PATH", "PythonPath", "VIRTUAL_ENV", "UV_PYTHON", "HOME"]);
+        assert_eq!(
+            filter(inherited.clone(), true, &["virtual_env"]),
+            names(&["PythonPath", "UV_PYTHON"])
+        );
+        assert!(filter(inherited, false, &[]).is_empty());
+    }
+
+    #[test]
+    fn keeps_index_and_tls_settings() {
+        let inherited = names(&[
+            "UV_INDEX_URL",
+            "uv_extra_index_url",
+            "UV_INDEX_CORP_PASSWORD",
+            "UV_NATIVE_TLS",
+            "UV_CACHE_DIR",
+        ]);
+        assert_eq!(filter(inherited, true, &[]), names(&["UV_CACHE_DIR"]));
+    }
+}
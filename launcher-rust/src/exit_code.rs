@@ -0,0 +1,85 @@
+use std::{fmt, process::ExitStatus};
+
+/// Exit codes reserved for failures inside the launcher, following the BSD sysexits values so
+/// they stay clear of the 0-2 range Python apps normally use. Anything else is the app's own code.
+pub const UNAVAILABLE: i32 = 69;
+pub const INTERNAL: i32 = 70;
+pub const OS_ERROR: i32 = 71;
+pub const TEMP_FAILURE: i32 = 75;
+pub const CONFIG: i32 = 78;
+
+/// Launcher failure class, attached as context so `main` can pick the matching exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    UvMissing,
+    BadState,
+    Bootstrap,
+    Spawn,
+}
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        match self {
+            Failure::UvMissing => UNAVAILABLE,
+            Failure::BadState => CONFIG,
+            Failure::Bootstrap => TEMP_FAILURE,
+            Failure::Spawn => OS_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Failure::UvMissing => "uv is missing",
+            Failure::BadState => "install state is invalid",
+            Failure::Bootstrap => "environment setup failed",
+            Failure::Spawn => "could not start the app",
+        };
+        f.write_str(label)
+    }
+}
+
+pub fn for_error(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<Failure>()
+        .map(|failure| failure.code())
+        .unwrap_or(INTERNAL)
+}
+
+/// The child's exit code, or `128 + signal` when it was killed by a signal.
+pub fn for_status(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    INTERNAL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn for_error_uses_failure_context() {
+        let err = Err::<(), _>(anyhow::anyhow!("uv.exe not found"))
+            .context(Failure::UvMissing)
+            .unwrap_err();
+        assert_eq!(for_error(&err), UNAVAILABLE);
+        assert_eq!(for_error(&anyhow::anyhow!("other")), INTERNAL);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn for_status_maps_signals() {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(for_status(ExitStatus::from_raw(3 << 8)), 3);
+        assert_eq!(for_status(ExitStatus::from_raw(9)), 137);
+    }
+}
@@ -0,0 +1,524 @@
+//! Single-instance guard, one per entry point: a named mutex on Windows, a locked file
+//! elsewhere. The first launcher also serves a local channel (a named pipe on Windows, a Unix
+//! socket elsewhere) and later launches forward their arguments to it, which the app
+//! receives by subscribing (see "Single instance" in README.md).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::instance_name;
+
+/// How long a later launch keeps trying to reach the first one, which may still be
+/// starting up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Forwarded launches kept until the app subscribes; older ones are dropped first.
+const MAX_PENDING: usize = 32;
+
+/// Held by the first instance for as long as it runs.
+pub struct Guard {
+    #[cfg(windows)]
+    handle: isize,
+    #[cfg(not(windows))]
+    _lock: std::fs::File,
+    socket: Option<PathBuf>,
+}
+
+/// Takes the guard, or returns `None` when another instance already runs; `args` and the
+/// working directory have then been handed to it.
+pub fn acquire(entry_name: Option<&str>, args: &[OsString]) -> Result<Option<Guard>> {
+    imp::acquire(&instance_name::for_entry(entry_name), args)
+}
+
+/// Marks the app as running when several instances are allowed, so that `lock` still sees
+/// it; `None` when a maintenance action holds the lock right now.
+pub fn mark_running(entry_name: Option<&str>) -> Result<Option<Guard>> {
+    imp::mark_running(&instance_name::for_entry(entry_name))
+}
+
+/// Takes the guard for maintenance that must not run next to the app, without serving or
+/// forwarding anything; `None` when the app is running.
+pub fn lock(entry_name: Option<&str>) -> Result<Option<Guard>> {
+    imp::lock(&instance_name::for_entry(entry_name))
+}
+
+impl Guard {
+    /// Socket or pipe the app subscribes to for forwarded launches, exported as
+    /// `UVESSEL_INSTANCE_SOCKET`.
+    pub fn socket(&self) -> Option<&Path> {
+        self.socket.as_deref()
+    }
+}
+
+/// One line of JSON on the instance socket.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    /// Sent by a later launch and relayed unchanged to subscribers.
+    Forward { cwd: String, args: Vec<String> },
+    /// Sent by the app; the connection then receives every forwarded launch.
+    Subscribe,
+}
+
+impl Message {
+    fn forward(args: &[OsString]) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Message::Forward {
+            cwd: cwd.to_string_lossy().to_string(),
+            args: crate::runner::forwarded_args(args)
+                .into_iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Sends this launch to the running instance at `socket`, retrying `connect` while that
+/// instance may still be starting up.
+fn forward<S: Write>(
+    socket: &Path,
+    args: &[OsString],
+    connect: impl Fn(&Path) -> std::io::Result<S>,
+) -> Result<()> {
+    let message = Message::forward(args);
+    let started = Instant::now();
+    let mut stream = loop {
+        match connect(socket) {
+            Ok(stream) => break stream,
+            Err(_) if started.elapsed() < CONNECT_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("forward arguments to the running instance at {}", socket.display())
+                });
+            }
+        }
+    };
+    writeln!(stream, "{}", serde_json::to_string(&message)?)
+        .with_context(|| format!("write to {}", socket.display()))?;
+    Ok(())
+}
+
+/// Relays forwarded launches to subscribers, buffering them while there are none.
+struct Broker<S> {
+    pending: Vec<String>,
+    subscribers: Vec<S>,
+}
+
+impl<S> Default for Broker<S> {
+    fn default() -> Self {
+        Broker {
+            pending: Vec::new(),
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl<S: Write> Broker<S> {
+    /// Acts on the one line a client sent over `stream`.
+    fn dispatch(&mut self, line: &str, mut stream: S) {
+        let line = line.trim();
+        match serde_json::from_str::<Message>(line) {
+            Ok(Message::Forward { .. }) => {
+                self.subscribers.retain_mut(|sub| writeln!(sub, "{line}").is_ok());
+                if self.subscribers.is_empty() {
+                    if self.pending.len() == MAX_PENDING {
+                        self.pending.remove(0);
+                    }
+                    self.pending.push(line.to_string());
+                }
+            }
+            Ok(Message::Subscribe) => {
+                for pending in self.pending.drain(..) {
+                    if writeln!(stream, "{pending}").is_err() {
+                        return;
+                    }
+                }
+                self.subscribers.push(stream);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{Broker, Guard, CONNECT_TIMEOUT};
+    use anyhow::{Context, Result};
+    use std::{
+        ffi::OsString,
+        fs::File,
+        io::{self, BufRead, BufReader},
+        iter::once,
+        os::windows::{
+            ffi::OsStrExt,
+            io::{AsRawHandle, FromRawHandle},
+        },
+        path::{Path, PathBuf},
+        sync::mpsc,
+        time::{Duration, Instant},
+    };
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, ERROR_PIPE_CONNECTED,
+        INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX,
+    };
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+    use windows_sys::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+    use windows_sys::Win32::System::Threading::CreateMutexW;
+
+    pub fn acquire(name: &str, args: &[OsString]) -> Result<Option<Guard>> {
+        let pipe = pipe_path(name);
+        let (handle, existed) = create_mutex(name);
+        if existed {
+            unsafe { CloseHandle(handle) };
+            super::forward(&pipe, args, |path| File::options().write(true).open(path))?;
+            return Ok(None);
+        }
+        let mut guard = Guard {
+            handle,
+            socket: None,
+        };
+        let first = create_first_pipe(&pipe)?;
+        let served = pipe.clone();
+        std::thread::spawn(move || serve(first, served));
+        guard.socket = Some(pipe);
+        Ok(Some(guard))
+    }
+
+    /// Every running instance keeps the mutex open, so it exists while any of them runs.
+    pub fn mark_running(name: &str) -> Result<Option<Guard>> {
+        let (handle, _) = create_mutex(name);
+        Ok(Some(Guard {
+            handle,
+            socket: None,
+        }))
+    }
+
+    pub fn lock(name: &str) -> Result<Option<Guard>> {
+        let (handle, existed) = create_mutex(name);
+        if existed {
+            unsafe { CloseHandle(handle) };
+            return Ok(None);
+        }
+        Ok(Some(Guard {
+            handle,
+            socket: None,
+        }))
+    }
+
+    /// The mutex handle (0 if it could not be created) and whether it already existed.
+    fn create_mutex(name: &str) -> (isize, bool) {
+        let name = format!("Local\\{name}");
+        let wide: Vec<u16> = std::ffi::OsStr::new(&name).encode_wide().chain(once(0)).collect();
+        let handle = unsafe { CreateMutexW(std::ptr::null_mut(), 0, wide.as_ptr()) };
+        let existed = handle != 0 && unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+        (handle, existed)
+    }
+
+    /// Pipe names are machine-wide, unlike the `Local\` mutex, so the session id keeps
+    /// users on a shared machine apart.
+    fn pipe_path(name: &str) -> PathBuf {
+        let mut session = 0u32;
+        unsafe { ProcessIdToSessionId(std::process::id(), &mut session) };
+        PathBuf::from(format!(r"\\.\pipe\{name}-{session}"))
+    }
+
+    /// The first pipe instance, which also proves no other process serves this name. An
+    /// instance that just released the mutex may still own it for a moment.
+    fn create_first_pipe(path: &Path) -> Result<File> {
+        let started = Instant::now();
+        loop {
+            match create_pipe(path, true) {
+                Ok(pipe) => return Ok(pipe),
+                Err(_) if started.elapsed() < CONNECT_TIMEOUT => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| format!("create {}", path.display()));
+                }
+            }
+        }
+    }
+
+    fn create_pipe(path: &Path, first: bool) -> io::Result<File> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(once(0)).collect();
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+        let pipe_mode = PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS;
+        let handle = unsafe {
+            CreateNamedPipeW(
+                wide.as_ptr(),
+                open_mode,
+                pipe_mode,
+                PIPE_UNLIMITED_INSTANCES,
+                4096,
+                4096,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { File::from_raw_handle(handle as _) })
+    }
+
+    /// Accepts clients one pipe instance after another. A pipe has no read timeout, so each
+    /// client's line is read on its own thread and handed to the broker when complete.
+    fn serve(mut pipe: File, path: PathBuf) {
+        let (lines, received) = mpsc::channel::<(String, File)>();
+        std::thread::spawn(move || {
+            let mut broker = Broker::default();
+            for (line, stream) in received {
+                broker.dispatch(&line, stream);
+            }
+        });
+        loop {
+            let connected = unsafe {
+                ConnectNamedPipe(pipe.as_raw_handle() as isize, std::ptr::null_mut()) != 0
+                    || GetLastError() == ERROR_PIPE_CONNECTED
+            };
+            let Ok(next) = create_pipe(&path, false) else {
+                return;
+            };
+            if connected {
+                let lines = lines.clone();
+                std::thread::spawn(move || {
+                    let mut line = String::new();
+                    if BufReader::new(&pipe).read_line(&mut line).is_ok() {
+                        let _ = lines.send((line, pipe));
+                    }
+                });
+            }
+            pipe = next;
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if self.handle != 0 {
+                unsafe { CloseHandle(self.handle) };
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use super::{Broker, Guard};
+    use anyhow::{bail, Context, Result};
+    use std::{
+        ffi::OsString,
+        fs::{self, File, TryLockError},
+        io::{BufRead, BufReader},
+        os::unix::{
+            fs::{DirBuilderExt, MetadataExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    pub fn acquire(name: &str, args: &[OsString]) -> Result<Option<Guard>> {
+        let dir = socket_dir()?;
+        let lock_path = dir.join(format!("{name}.lock"));
+        let socket = dir.join(format!("{name}.sock"));
+        let lock = open_lock(&lock_path)?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                super::forward(&socket, args, |path| UnixStream::connect(path))?;
+                return Ok(None);
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(err).with_context(|| format!("lock {}", lock_path.display()));
+            }
+        }
+
+        // Holding the lock means any socket file left here belongs to a dead instance.
+        let _ = fs::remove_file(&socket);
+        let listener =
+            UnixListener::bind(&socket).with_context(|| format!("bind {}", socket.display()))?;
+        std::thread::spawn(move || serve(listener));
+        Ok(Some(Guard {
+            _lock: lock,
+            socket: Some(socket),
+        }))
+    }
+
+    /// A shared lock: instances do not exclude each other, only `lock`.
+    pub fn mark_running(name: &str) -> Result<Option<Guard>> {
+        let lock_path = socket_dir()?.join(format!("{name}.lock"));
+        let lock = open_lock(&lock_path)?;
+        match lock.try_lock_shared() {
+            Ok(()) => Ok(Some(Guard {
+                _lock: lock,
+                socket: None,
+            })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => {
+                Err(err).with_context(|| format!("lock {}", lock_path.display()))
+            }
+        }
+    }
+
+    pub fn lock(name: &str) -> Result<Option<Guard>> {
+        let lock_path = socket_dir()?.join(format!("{name}.lock"));
+        let lock = open_lock(&lock_path)?;
+        match lock.try_lock() {
+            Ok(()) => Ok(Some(Guard {
+                _lock: lock,
+                socket: None,
+            })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => {
+                Err(err).with_context(|| format!("lock {}", lock_path.display()))
+            }
+        }
+    }
+
+    fn open_lock(path: &Path) -> Result<File> {
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("open {}", path.display()))
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            // The lock itself is released when the file closes.
+            if let Some(socket) = &self.socket {
+                let _ = fs::remove_file(socket);
+            }
+        }
+    }
+
+    fn serve(listener: UnixListener) {
+        let mut broker = Broker::default();
+        for stream in listener.incoming().flatten() {
+            // A client that never sends its line must not stall the others.
+            let timeout = Some(Duration::from_secs(2));
+            let _ = stream.set_read_timeout(timeout);
+            let _ = stream.set_write_timeout(timeout);
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_ok() {
+                broker.dispatch(&line, stream);
+            }
+        }
+    }
+
+    /// Per-user directory for the lock and socket: `$XDG_RUNTIME_DIR`, else a private
+    /// directory under the temp dir.
+    fn socket_dir() -> Result<PathBuf> {
+        if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
+        let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+        let dir = std::env::temp_dir().join(format!("uvessel-{user}"));
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)
+            .with_context(|| format!("create {}", dir.display()))?;
+        ensure_private(&dir)?;
+        Ok(dir)
+    }
+
+    /// The temp dir is shared, so the directory may predate us: refuse one that another
+    /// user could read or plant a socket in.
+    fn ensure_private(dir: &Path) -> Result<()> {
+        let meta = fs::symlink_metadata(dir).with_context(|| format!("stat {}", dir.display()))?;
+        let uid = unsafe { libc::getuid() };
+        if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o777 != 0o700 {
+            bail!(
+                "{} is not a private directory of this user (owner uid {}, mode {:o}); remove it \
+                 or set XDG_RUNTIME_DIR",
+                dir.display(),
+                meta.uid(),
+                meta.mode() & 0o7777
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::Message;
+        use super::*;
+        use std::io::Write;
+
+        fn send(socket: &Path, message: &Message) -> UnixStream {
+            let mut stream = UnixStream::connect(socket).unwrap();
+            writeln!(stream, "{}", serde_json::to_string(message).unwrap()).unwrap();
+            stream
+        }
+
+        #[test]
+        fn forwarded_launch_reaches_a_later_subscriber() {
+            let tmp = tempfile::tempdir().unwrap();
+            let socket = tmp.path().join("app.sock");
+            let listener = UnixListener::bind(&socket).unwrap();
+            std::thread::spawn(move || serve(listener));
+
+            let args = [OsString::from("report.txt"), OsString::from("--uvessel-info")];
+            let forwarded = Message::forward(&args);
+            drop(send(&socket, &forwarded));
+            let subscriber = send(&socket, &Message::Subscribe);
+            let mut line = String::new();
+            BufReader::new(subscriber).read_line(&mut line).unwrap();
+
+            let received: Message = serde_json::from_str(&line).unwrap();
+            assert_eq!(received, forwarded);
+            let Message::Forward { args, .. } = received else {
+                unreachable!()
+            };
+            assert_eq!(args, vec!["report.txt"]);
+        }
+
+        #[test]
+        fn shared_socket_dir_is_refused() {
+            use std::os::unix::fs::PermissionsExt;
+            let tmp = tempfile::tempdir().unwrap();
+            let dir = tmp.path().join("uvessel-user");
+            fs::create_dir(&dir).unwrap();
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+            assert!(ensure_private(&dir).is_ok());
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+            assert!(ensure_private(&dir).is_err());
+            let link = tmp.path().join("link");
+            std::os::unix::fs::symlink(&dir, &link).unwrap();
+            assert!(ensure_private(&link).is_err());
+        }
+
+        #[test]
+        fn lock_is_refused_while_an_instance_runs() {
+            let name = format!("Uvessel-test-{}", std::process::id());
+            let running = mark_running(&name).unwrap().unwrap();
+            let second = mark_running(&name).unwrap().unwrap();
+            assert!(lock(&name).unwrap().is_none());
+            drop((running, second));
+
+            let maintenance = lock(&name).unwrap().unwrap();
+            assert!(mark_running(&name).unwrap().is_none());
+            drop(maintenance);
+            let _ = fs::remove_file(socket_dir().unwrap().join(format!("{name}.lock")));
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Name of an entry point's single-instance lock. The installer includes this file with
+//! `#[path]` to tell whether the app is running, so both always agree on it.
+
+use crate::config;
+
+/// `Uvessel-<app id>` with an `-<entry>` suffix for named entry points.
+pub fn for_entry(entry_name: Option<&str>) -> String {
+    let id = config::APP_ID.trim();
+    let fallback = if !config::PRODUCT_NAME.trim().is_empty() {
+        config::PRODUCT_NAME
+    } else {
+        config::NAME
+    };
+    let base = if id.is_empty() { fallback } else { id };
+    let cleaned: String = base
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' { ch } else { '_' })
+        .collect();
+    match entry_name {
+        Some(entry) => format!("Uvessel-{}-{}", cleaned, entry),
+        None => format!("Uvessel-{}", cleaned),
+    }
+}
@@ -1,25 +1,239 @@
 use anyhow::{Context, Result};
 use std::{
+    collections::VecDeque,
     fs,
+    io::{BufRead, BufReader, Read, Write},
     path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    sync::Mutex,
+    time::Instant,
 };
 
+use crate::config;
+use crate::supervise;
+use crate::timestamp;
+
 const LOG_FILE_NAME: &str = "launcher.log";
+const APP_LOG_FILE_NAME: &str = "app.log";
+/// Lines of the app's stderr kept for a crash record.
+const STDERR_TAIL_LINES: usize = 200;
 
 pub fn logs_dir(root: &Path) -> PathBuf {
     root.join(".runtime").join("logs")
 }
 
-pub fn init(root: &Path) -> Result<PathBuf> {
+/// Where a child's output goes: teed into launcher.log for uv bootstrap steps or app.log for
+/// the app, or straight to the console for console-mode apps that need a real terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sink {
+    Launcher,
+    App,
+    Console,
+}
+
+/// Launcher log plus the app output log, both rotated by size.
+#[derive(Debug)]
+pub struct Logger {
+    launcher: Mutex<RotatingFile>,
+    app: Mutex<RotatingFile>,
+    stderr_tail: Mutex<VecDeque<String>>,
+}
+
+pub fn init(root: &Path) -> Result<Logger> {
     let dir = logs_dir(root);
     fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
-    let log_path = dir.join(LOG_FILE_NAME);
-    let _file = fs::OpenOptions::new()
+    let max_bytes = config::LOG_MAX_BYTES;
+    let keep = config::LOG_RETENTION;
+    let launcher = RotatingFile::open(dir.join(LOG_FILE_NAME), max_bytes, keep)?;
+    let app = RotatingFile::open(dir.join(APP_LOG_FILE_NAME), max_bytes, keep)?;
+    Ok(Logger {
+        launcher: Mutex::new(launcher),
+        app: Mutex::new(app),
+        stderr_tail: Mutex::new(VecDeque::new()),
+    })
+}
+
+impl Logger {
+    pub fn info(&self, message: &str) {
+        self.write(Sink::Launcher, &format!("{} {message}", timestamp::now()));
+    }
+
+    pub fn error(&self, message: &str) {
+        self.write(Sink::Launcher, &format!("{} error: {message}", timestamp::now()));
+    }
+
+    /// The last lines the app wrote to stderr since the previous call, oldest first; empty
+    /// for `Sink::Console`.
+    pub fn take_app_stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|mut tail| tail.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, sink: Sink, line: &str) {
+        let file = match sink {
+            Sink::Launcher => &self.launcher,
+            Sink::App | Sink::Console => &self.app,
+        };
+        if let Ok(mut file) = file.lock() {
+            let _ = file.write_line(line);
+        }
+    }
+
+    /// Runs `cmd` to completion with its output routed to `sink`, and records the command
+    /// line, duration and exit code in launcher.log.
+    pub fn run_logged(&self, cmd: &mut Command, sink: Sink) -> Result<ExitStatus> {
+        self.info(&format!("> {}", format_command(cmd)));
+        let start = Instant::now();
+        let status = if sink == Sink::Console {
+            cmd.stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit());
+            let (mut child, _tracked) = supervise::spawn(cmd).context("spawn command")?;
+            child.wait().context("wait for command")?
+        } else {
+            self.run_teed(cmd, sink)?
+        };
+        self.info(&format!(
+            "exit code {} after {:.1}s",
+            status
+                .code()
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            start.elapsed().as_secs_f64()
+        ));
+        Ok(status)
+    }
+
+    /// Tees the child's stdout/stderr into `sink` and our own stdio.
+    fn run_teed(&self, cmd: &mut Command, sink: Sink) -> Result<ExitStatus> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let (mut child, _tracked) = supervise::spawn(cmd).context("spawn command")?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let status = std::thread::scope(|scope| {
+            if let Some(pipe) = stdout {
+                scope.spawn(|| self.tee(pipe, sink, false));
+            }
+            if let Some(pipe) = stderr {
+                scope.spawn(|| self.tee(pipe, sink, true));
+            }
+            child.wait().context("wait for command")
+        })?;
+        Ok(status)
+    }
+
+    fn tee(&self, pipe: impl Read, sink: Sink, is_stderr: bool) {
+        let mut reader = BufReader::new(pipe);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            // Our stdio is detached in windowed builds, so these writes are best effort.
+            if is_stderr {
+                let _ = std::io::stderr().write_all(&buf);
+            } else {
+                let _ = std::io::stdout().write_all(&buf);
+            }
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if is_stderr && sink == Sink::App {
+                if let Ok(mut tail) = self.stderr_tail.lock() {
+                    if tail.len() == STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.to_string());
+                }
+            }
+            self.write(sink, line);
+        }
+    }
+}
+
+/// Append-only log file that rolls over to `<name>.1` .. `<name>.<keep>` once it exceeds
+/// `max_bytes`.
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    file: fs::File,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, keep: usize) -> Result<Self> {
+        let file = open_append(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        if self.keep == 0 {
+            fs::write(&self.path, "").with_context(|| format!("truncate {}", self.path.display()))?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.keep));
+            for i in (1..self.keep).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    let _ = fs::rename(&from, rotated_path(&self.path, i + 1));
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))
+                .with_context(|| format!("rotate {}", self.path.display()))?;
+        }
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<fs::File> {
+    fs::OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&log_path)
-        .with_context(|| format!("open {}", log_path.display()))?;
-    Ok(log_path)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+fn format_command(cmd: &Command) -> String {
+    let program = cmd.get_program().to_string_lossy();
+    let args = cmd
+        .get_args()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if args.is_empty() {
+        program.to_string()
+    } else {
+        format!("{program} {args}")
+    }
 }
 
 #[cfg(test)]
@@ -29,8 +243,42 @@ mod tests {
     #[test]
     fn init_creates_log_file() {
         let tmp = tempfile::tempdir().unwrap();
-        let log_path = init(tmp.path()).unwrap();
-        assert!(log_path.exists());
-        assert_eq!(log_path, logs_dir(tmp.path()).join(LOG_FILE_NAME));
+        let log = init(tmp.path()).unwrap();
+        log.info("starting");
+        let log_path = logs_dir(tmp.path()).join(LOG_FILE_NAME);
+        assert!(fs::read_to_string(log_path).unwrap().contains("starting"));
+        assert!(logs_dir(tmp.path()).join(APP_LOG_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn rotating_file_keeps_retention_count() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("app.log");
+        let mut file = RotatingFile::open(path.clone(), 10, 2).unwrap();
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_logged_tees_child_output() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = init(tmp.path()).unwrap();
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg("echo out; echo err >&2; exit 3");
+        let status = log.run_logged(&mut cmd, Sink::App).unwrap();
+        assert_eq!(status.code(), Some(3));
+
+        let app = fs::read_to_string(logs_dir(tmp.path()).join(APP_LOG_FILE_NAME)).unwrap();
+        assert!(app.contains("out") && app.contains("err"));
+        assert_eq!(log.take_app_stderr_tail(), vec!["err"]);
+        assert!(log.take_app_stderr_tail().is_empty());
+        let launcher = fs::read_to_string(logs_dir(tmp.path()).join(LOG_FILE_NAME)).unwrap();
+        assert!(launcher.contains("exit code 3"));
     }
 }
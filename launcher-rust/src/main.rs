@@ -1,80 +1,75 @@
-#![cfg_attr(windows, windows_subsystem = "windows")]
+#![cfg_attr(all(windows, not(feature = "console")), windows_subsystem = "windows")]
 
+mod app_env;
+mod crash;
+mod diagnostics;
+mod entry_name;
+// Shared with the installer, which also uses the entry point parsing.
+#[allow(dead_code)]
+#[path = "../../installer-rust/src/entry_spec.rs"]
+mod entry_spec;
+mod env_lock;
+mod env_policy;
+mod exit_code;
+mod instance;
+mod instance_name;
 mod logging;
+mod maintenance;
 mod paths;
+mod placeholders;
 mod runner;
 mod state;
+mod supervise;
+mod timestamp;
 mod config;
 
 use anyhow::Result;
 
-fn main() -> Result<()> {
-    let _single_instance = acquire_single_instance();
-    if _single_instance.is_none() {
-        return Ok(());
-    }
-
-    let root = paths::root_dir()?;
-    logging::init(&root)?;
-    runner::run(&root)
-}
-
-#[cfg(windows)]
-fn acquire_single_instance() -> Option<SingleInstanceGuard> {
-    use std::ffi::OsStr;
-    use std::iter::once;
-    use std::os::windows::ffi::OsStrExt;
-    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_ALREADY_EXISTS};
-    use windows_sys::Win32::System::Threading::CreateMutexW;
-
-    let name = mutex_name();
-    let wide: Vec<u16> = OsStr::new(&name).encode_wide().chain(once(0)).collect();
-    let handle = unsafe { CreateMutexW(std::ptr::null_mut(), 0, wide.as_ptr()) };
-    if handle == 0 {
-        return Some(SingleInstanceGuard { handle });
-    }
-    let last_error = unsafe { windows_sys::Win32::Foundation::GetLastError() };
-    if last_error == ERROR_ALREADY_EXISTS {
-        unsafe { CloseHandle(handle) };
-        return None;
-    }
-    Some(SingleInstanceGuard { handle })
-}
-
-#[cfg(not(windows))]
-fn acquire_single_instance() -> Option<SingleInstanceGuard> {
-    Some(SingleInstanceGuard {})
+fn main() {
+    // `process::exit` skips destructors, so everything is dropped inside `run` first.
+    let code = match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            exit_code::for_error(&err)
+        }
+    };
+    std::process::exit(code);
 }
 
-#[cfg(windows)]
-fn mutex_name() -> String {
-    let id = config::APP_ID.trim();
-    let fallback = if !config::PRODUCT_NAME.trim().is_empty() {
-        config::PRODUCT_NAME
+fn run() -> Result<i32> {
+    // Named entry points are separate programs, so each gets its own instance lock.
+    let entry_name = entry_name::current()?;
+    let args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    let action = maintenance::Action::from_args(&args);
+    // Maintenance runs next to a running app instead of being forwarded to it.
+    let instance_guard = if action != maintenance::Action::Run {
+        maintenance::attach_parent_console();
+        None
+    } else if config::SINGLE_INSTANCE {
+        match instance::acquire(entry_name.as_deref(), &args)? {
+            Some(guard) => Some(guard),
+            None => return Ok(0),
+        }
     } else {
-        config::NAME
+        // Lets `--uvessel-reset-env` see the app running even without the single-instance lock.
+        instance::mark_running(entry_name.as_deref())?
     };
-    let base = if id.is_empty() { fallback } else { id };
-    let cleaned: String = base
-        .chars()
-        .map(|ch| if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' { ch } else { '_' })
-        .collect();
-    format!("Local\\Uvessel-{}", cleaned)
-}
 
-#[cfg(windows)]
-struct SingleInstanceGuard {
-    handle: isize,
-}
-
-#[cfg(windows)]
-impl Drop for SingleInstanceGuard {
-    fn drop(&mut self) {
-        if self.handle != 0 {
-            unsafe { windows_sys::Win32::Foundation::CloseHandle(self.handle) };
+    let root = paths::root_dir()?;
+    let log = logging::init(&root)?;
+    log.info(&format!("launcher started from {}", root.display()));
+    let socket = instance_guard.as_ref().and_then(instance::Guard::socket);
+    match runner::run(&root, &log, entry_name.as_deref(), &args, socket) {
+        Ok(code) => {
+            if code != 0 {
+                log.info(&format!("app exited with code {code}"));
+            }
+            Ok(code)
+        }
+        Err(err) => {
+            log.error(&format!("{err:#}"));
+            Err(err)
         }
     }
 }
-
-#[cfg(not(windows))]
-struct SingleInstanceGuard {}
@@ -0,0 +1,132 @@
+//! Reserved `--uvessel-*` flags that maintain the install instead of running the app.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::{ffi::OsString, path::Path};
+
+use crate::logging::Logger;
+use crate::state;
+
+/// What one launch does; every action but `Run` leaves the app alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Run,
+    /// `--uvessel-info`: print versions, paths and state as JSON.
+    Info,
+    /// `--uvessel-diagnose`: write a support bundle zip and print its path.
+    Diagnose,
+    /// `--uvessel-reset-env`: delete the venv and sync it from scratch.
+    ResetEnv,
+    /// `--uvessel-shell`: a Python REPL in the app's environment.
+    Shell,
+    /// `--uvessel-sync`: sync the venv even though it looks current.
+    Sync,
+}
+
+impl Action {
+    /// The first maintenance flag in `args`; unknown reserved flags are ignored.
+    pub fn from_args(args: &[OsString]) -> Self {
+        args.iter()
+            .find_map(|arg| match arg.to_str()? {
+                "--uvessel-info" => Some(Action::Info),
+                "--uvessel-diagnose" => Some(Action::Diagnose),
+                "--uvessel-reset-env" => Some(Action::ResetEnv),
+                "--uvessel-shell" => Some(Action::Shell),
+                "--uvessel-sync" => Some(Action::Sync),
+                _ => None,
+            })
+            .unwrap_or(Action::Run)
+    }
+}
+
+/// Versions, paths and install state; a state that cannot be read is reported, not fatal.
+pub fn info(root: &Path, entry_name: Option<&str>) -> Value {
+    let runtime = root.join(".runtime");
+    let state_path = state::state_path(root);
+    let (state, projects) = match state::read_state(&state_path) {
+        Ok(st) => {
+            let projects: Vec<Value> = st
+                .projects
+                .iter()
+                .map(|project| {
+                    let dir = root.join(&project.rel);
+                    let venv = state::venv_dir(&runtime, &project.name);
+                    let in_sync = state::Fingerprint::read(&dir, &venv)
+                        .is_ok_and(|current| current == project.fingerprint)
+                        && venv.join("pyvenv.cfg").exists();
+                    json!({
+                        "name": project.name,
+                        "dir": dir,
+                        "venv": venv,
+                        "python": project.fingerprint.interpreter,
+                        "in_sync": in_sync,
+                    })
+                })
+                .collect();
+            (json!(st), projects)
+        }
+        Err(err) => (json!({ "error": format!("{err:#}") }), Vec::new()),
+    };
+    json!({
+        "app_id": crate::config::APP_ID,
+        "launcher_version": env!("CARGO_PKG_VERSION"),
+        "env_version": crate::app_env::ENV_VERSION,
+        "entry_point": entry_name,
+        "paths": {
+            "root": root,
+            "runtime": runtime,
+            "uv": root.join("uv.exe"),
+            "state": state_path,
+            "logs": crate::logging::logs_dir(root),
+        },
+        "projects": projects,
+        "state": state,
+    })
+}
+
+pub fn print_info(root: &Path, entry_name: Option<&str>) -> Result<i32> {
+    println!("{}", serde_json::to_string_pretty(&info(root, entry_name))?);
+    Ok(0)
+}
+
+pub fn print_diagnostics(root: &Path, log: &Logger) -> Result<i32> {
+    let bundle = crate::diagnostics::write_bundle(root, "launcher", &[])?;
+    log.info(&format!("wrote diagnostics to {}", bundle.display()));
+    println!("{}", bundle.display());
+    Ok(0)
+}
+
+/// Windowed launchers have no console; attach to the terminal they were started from so
+/// maintenance output and the shell show up there.
+pub fn attach_parent_console() {
+    #[cfg(all(windows, not(feature = "console")))]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_maintenance_flag_wins() {
+        let args = |list: &[&str]| list.iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(Action::from_args(&args(&["file.txt"])), Action::Run);
+        assert_eq!(Action::from_args(&args(&["--uvessel-x", "--uvessel-sync"])), Action::Sync);
+        assert_eq!(
+            Action::from_args(&args(&["--uvessel-shell", "--uvessel-info"])),
+            Action::Shell
+        );
+    }
+
+    #[test]
+    fn info_reports_unreadable_state() {
+        let tmp = tempfile::tempdir().unwrap();
+        let info = info(tmp.path(), Some("cli"));
+        assert_eq!(info["entry_point"], "cli");
+        assert!(info["state"]["error"].is_string());
+        assert_eq!(info["projects"], json!([]));
+    }
+}
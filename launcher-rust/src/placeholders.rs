@@ -0,0 +1,69 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::entry_spec;
+
+/// Values for the placeholders allowed in entry point arguments.
+pub struct Context<'a> {
+    pub install_root: &'a Path,
+    pub version: &'a str,
+}
+
+impl Context<'_> {
+    fn lookup(&self, name: &str) -> Option<String> {
+        let path = |p: &Path| p.to_string_lossy().to_string();
+        match name {
+            "install_root" => Some(path(self.install_root)),
+            "data_dir" => Some(path(&self.install_root.join("data"))),
+            "assets_dir" => Some(path(&self.install_root.join("assets"))),
+            "version" => Some(self.version.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Replaces `{name}` placeholders in `arg`; `{{` and `}}` stand for literal braces.
+pub fn expand(arg: &str, ctx: &Context) -> Result<String> {
+    entry_spec::expand(arg, |name| ctx.lookup(name)).map_err(anyhow::Error::msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_known_placeholders() {
+        let root = Path::new("install");
+        let ctx = Context {
+            install_root: root,
+            version: "1.2.3",
+        };
+        assert_eq!(
+            expand("--config={data_dir}", &ctx).unwrap(),
+            format!("--config={}", root.join("data").display())
+        );
+        assert_eq!(expand("v{version}", &ctx).unwrap(), "v1.2.3");
+        assert_eq!(expand("{{raw}}", &ctx).unwrap(), "{raw}");
+    }
+
+    #[test]
+    fn rejects_unknown_or_unclosed_placeholders() {
+        let ctx = Context {
+            install_root: Path::new("install"),
+            version: "1.0.0",
+        };
+        assert!(expand("{home}", &ctx).is_err());
+        assert!(expand("{install_root", &ctx).is_err());
+    }
+
+    #[test]
+    fn every_placeholder_the_installer_accepts_expands() {
+        let ctx = Context {
+            install_root: Path::new("install"),
+            version: "1.0.0",
+        };
+        for name in entry_spec::PLACEHOLDERS {
+            assert!(ctx.lookup(name).is_some(), "{name}");
+        }
+    }
+}
@@ -1,88 +1,449 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use std::{
+    ffi::{OsStr, OsString},
     fs,
     path::{Path, PathBuf},
     process::{Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
 };
 
+use crate::app_env;
+use crate::crash;
+use crate::env_lock::EnvLock;
+use crate::env_policy;
+use crate::exit_code::{self, Failure};
+use crate::instance;
+use crate::logging::{Logger, Sink};
+use crate::maintenance::{self, Action};
+use crate::placeholders;
 use crate::state::{self, EntryPoint, State};
+use crate::supervise::{self, Decision, RestartPolicy, Supervisor};
 
-pub fn run(root: &Path) -> Result<()> {
-    run_with_executor(root, |cmd| cmd.status().context("spawn command"))
+/// Prefix for flags handled by the launcher itself; these are never passed to the app.
+const RESERVED_PREFIX: &str = "--uvessel-";
+
+/// Console shims (built with the `console` feature) share their terminal with the app.
+const CONSOLE: bool = cfg!(feature = "console");
+
+/// How long a launch waits for an installer or another launcher to finish a sync.
+const ENV_LOCK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Runs the app, or the maintenance action picked by a reserved flag, and returns its exit
+/// code; launcher failures carry a `Failure` context.
+/// `instance_socket` is the single-instance socket to export to the app, if any.
+pub fn run(
+    root: &Path,
+    log: &Logger,
+    entry_name: Option<&str>,
+    args: &[OsString],
+    instance_socket: Option<&Path>,
+) -> Result<i32> {
+    supervise::forward_termination_signals();
+    let result = run_with_executor(root, log, entry_name, args, instance_socket, |cmd, sink| {
+        log.run_logged(cmd, sink)
+    });
+    supervise::release_children();
+    result
 }
 
 pub fn run_with_executor(
     root: &Path,
-    mut exec: impl FnMut(&mut Command) -> Result<ExitStatus>,
-) -> Result<()> {
+    log: &Logger,
+    entry_name: Option<&str>,
+    args: &[OsString],
+    instance_socket: Option<&Path>,
+    mut exec: impl FnMut(&mut Command, Sink) -> Result<ExitStatus>,
+) -> Result<i32> {
+    let launch_started = Instant::now();
+    let action = Action::from_args(args);
+    match action {
+        Action::Info => return maintenance::print_info(root, entry_name),
+        Action::Diagnose => return maintenance::print_diagnostics(root, log),
+        _ => {}
+    }
     let uv = root.join("uv.exe");
     if !uv.exists() {
-        bail!("uv.exe not found next to launcher at {}", uv.display());
+        return Err(anyhow!("uv.exe not found next to launcher at {}", uv.display()))
+            .context(Failure::UvMissing);
     }
 
     let runtime = root.join(".runtime");
     ensure_runtime_dirs(&runtime)?;
+    // Held while the venv and state.json may change; released before the app starts.
+    let env_lock = EnvLock::acquire(&runtime, "launcher", ENV_LOCK_TIMEOUT, |msg| log.info(msg))
+        .context(Failure::Bootstrap)?;
 
     let state_path = state::state_path(root);
-    let mut st = state::read_state(&state_path)?;
-    let proj = resolve_project(root, &st)?;
-
-    let lock_path = proj.join("uv.lock");
-    let lock_mtime = if lock_path.exists() {
-        state::file_mtime_unix(&lock_path)?
-    } else {
-        0
+    let mut st = state::read_state(&state_path).context(Failure::BadState)?;
+    let (entry, entry_args, project) = st.entry_for(entry_name).context(Failure::BadState)?;
+    let (entry, entry_args, project) = (entry.clone(), entry_args.to_vec(), project.to_string());
+    let proj = resolve_project(root, &st, &project).context(Failure::BadState)?;
+    let venv = state::venv_dir(&runtime, &project);
+    let version = st.launcher_version.clone();
+    let ctx = placeholders::Context {
+        install_root: root,
+        version: &version,
     };
+    let launch_id = app_env::new_launch_id();
+    log.info(&format!("launch id {launch_id}"));
+    let mut app_env = app_env::vars(root, crate::config::APP_ID, &version, &launch_id);
+    for (key, value) in crate::config::ENV_VARS {
+        let value = placeholders::expand(value, &ctx).context(Failure::BadState)?;
+        app_env.push((key.to_string(), value));
+    }
+    if let Some(socket) = instance_socket {
+        let socket = socket.to_string_lossy().to_string();
+        app_env.push(("UVESSEL_INSTANCE_SOCKET".to_string(), socket));
+    }
 
-    if needs_bootstrap(&runtime, lock_mtime, st.lock_mtime_unix) {
-        run_with_retry(
-            || {
-                let mut install = build_uv_cmd(&uv, &proj, &runtime);
-                install.arg("python").arg("install");
-                if let Some(version) = read_python_version(&proj)? {
-                    install.arg(version);
-                }
-                exec(&mut install)
-            },
-            5,
-            "uv python install",
-        )?;
-
-        run_with_retry(
-            || {
-                let mut sync = build_uv_cmd(&uv, &proj, &runtime);
-                sync.arg("sync");
-                if lock_path.exists() {
-                    sync.arg("--frozen");
+    let uv_env = UvEnv {
+        uv: &uv,
+        proj: &proj,
+        runtime: &runtime,
+        venv: &venv,
+        app_env: &app_env,
+    };
+    // Held until the reset is done, so the app can't start on a half-deleted venv.
+    let _instances = match action {
+        Action::ResetEnv => lock_instances(&st, &project, &[], "resetting")?,
+        _ => Vec::new(),
+    };
+    let current = state::Fingerprint::read(&proj, &venv)?;
+    let mut synced = match action {
+        Action::ResetEnv => {
+            log.info("resetting the environment");
+            remove_venv(&venv)?;
+            true
+        }
+        Action::Sync => true,
+        _ => needs_bootstrap(&venv, &current, &st.project(&project)?.fingerprint),
+    };
+    if synced {
+        sync_env(&uv_env, false, log, &mut exec).context(Failure::Bootstrap)?;
+    } else if !venv_intact(&venv, &entry) {
+        log.error("the environment is missing files; recreating it");
+        let others = lock_instances(&st, &project, &[entry_name], "recreating")
+            .context(Failure::Bootstrap)?;
+        repair_env(&uv_env, &entry, log, &mut exec)?;
+        drop(others);
+        synced = true;
+    }
+    if synced {
+        st.project_mut(&project)?.fingerprint = state::Fingerprint::read(&proj, &venv)?;
+        state::write_state(&state_path, &st)?;
+    }
+    drop(env_lock);
+    match action {
+        Action::ResetEnv | Action::Sync => return Ok(0),
+        Action::Shell => {
+            let mut shell = shell_cmd(&uv_env);
+            let status = exec(&mut shell, Sink::Console).context(Failure::Spawn)?;
+            return Ok(exit_code::for_status(status));
+        }
+        Action::Run | Action::Info | Action::Diagnose => {}
+    }
+
+    let entry_args = entry_args
+        .iter()
+        .map(|arg| placeholders::expand(arg, &ctx))
+        .collect::<Result<Vec<_>>>()
+        .context(Failure::BadState)?;
+    // A venv that still matches its fingerprint is run directly; `uv run` would re-check
+    // the project first, which only pays off right after a sync.
+    let app_cmd = |synced: bool| {
+        let direct = if synced {
+            None
+        } else {
+            direct_cmd(&venv, &proj, &entry, &app_env)
+        };
+        let (mut cmd, via) = match direct {
+            Some(cmd) => (cmd, "venv interpreter"),
+            None => {
+                let mut cmd = build_uv_cmd(&uv, &proj, &runtime, &venv, &app_env);
+                cmd.arg("run");
+                match &entry {
+                    EntryPoint::PythonFile(f) => cmd.arg("python").arg(f),
+                    EntryPoint::Module(m) => cmd.arg("python").arg("-m").arg(m),
+                    EntryPoint::Command(c) => cmd.arg(c),
+                };
+                (cmd, "uv run")
+            }
+        };
+        cmd.args(&entry_args).args(forwarded_args(args));
+        // Dumps the Python stack to stderr on a hard crash, where the crash record finds it.
+        cmd.env("PYTHONFAULTHANDLER", "1");
+        (cmd, via)
+    };
+    let (mut run_cmd, via) = app_cmd(synced);
+
+    log.info(&format!(
+        "starting app via {via}, {}ms after launch",
+        launch_started.elapsed().as_millis()
+    ));
+    let sink = if CONSOLE { Sink::Console } else { Sink::App };
+    let mut python = st.project(&project).ok().map(|p| p.fingerprint.interpreter.clone());
+    let mut supervisor = Supervisor::new(RestartPolicy::from_config());
+    loop {
+        let started = Instant::now();
+        let status = exec(&mut run_cmd, sink).context(Failure::Spawn)?;
+        let code = exit_code::for_status(status);
+        let ran_for = started.elapsed();
+        let stderr_tail = log.take_app_stderr_tail();
+        // Launches skip the health probe; a failure from an unsynced venv is when it runs.
+        if code != 0
+            && !synced
+            && !supervise::stop_requested()
+            && !probe_env(&uv_env, &entry, &mut exec)
+        {
+            log.error(&format!(
+                "app exited with code {code} and the health probe failed; recreating the environment"
+            ));
+            let env_lock =
+                EnvLock::acquire(&runtime, "launcher", ENV_LOCK_TIMEOUT, |msg| log.info(msg))
+                    .context(Failure::Bootstrap)?;
+            let others = lock_instances(&st, &project, &[entry_name], "recreating")
+                .context(Failure::Bootstrap)?;
+            repair_env(&uv_env, &entry, log, &mut exec)?;
+            let fingerprint = state::Fingerprint::read(&proj, &venv)?;
+            python = Some(fingerprint.interpreter.clone());
+            st.project_mut(&project)?.fingerprint = fingerprint;
+            state::write_state(&state_path, &st)?;
+            drop((others, env_lock));
+            synced = true;
+            run_cmd = app_cmd(synced).0;
+            log.info("restarting the app via uv run");
+            continue;
+        }
+        let decision = supervisor.after_exit(code, ran_for, Instant::now());
+        // An app stopped on request did not crash.
+        if code != 0 && !supervise::stop_requested() {
+            let crash = crash::Crash {
+                exit_code: code,
+                entry_point: entry_name,
+                launch_id: &launch_id,
+                app_version: &version,
+                python: python.as_deref(),
+                stderr_tail,
+            };
+            // Console apps already showed their traceback in the terminal, and an app about
+            // to be restarted needs no error window.
+            let restarting = matches!(decision, Decision::Restart { .. });
+            crash::report(root, log, &crash, !CONSOLE && !restarting);
+        }
+        match decision {
+            Decision::Exit => return Ok(code),
+            Decision::GiveUp(reason) => {
+                log.error(&format!("app exited with code {code}; not restarting: {reason}"));
+                return Ok(code);
+            }
+            Decision::Restart { attempt, delay } => {
+                log.info(&format!(
+                    "app exited with code {code} after {:.1}s; restart {attempt} in {}ms",
+                    ran_for.as_secs_f64(),
+                    delay.as_millis()
+                ));
+                if !supervise::wait_unless_stopped(delay) {
+                    log.info("termination requested; not restarting the app");
+                    return Ok(code);
                 }
-                exec(&mut sync)
-            },
-            5,
-            "uv sync",
-        )?;
+            }
+        }
+    }
+}
 
-        st.lock_mtime_unix = lock_mtime;
-        state::write_state(&state_path, &st)?;
+/// Everything needed to run uv against one project and its venv.
+struct UvEnv<'a> {
+    uv: &'a Path,
+    proj: &'a Path,
+    runtime: &'a Path,
+    venv: &'a Path,
+    app_env: &'a [(String, String)],
+}
 
-        cleanup_uv_cache(&runtime)?;
+impl UvEnv<'_> {
+    fn cmd(&self) -> Command {
+        build_uv_cmd(self.uv, self.proj, self.runtime, self.venv, self.app_env)
     }
+}
 
-    let mut run_cmd = build_uv_cmd(&uv, &proj, &runtime);
-    run_cmd.arg("run");
-    match &st.entry {
-        EntryPoint::PythonFile(f) => run_cmd.arg("python").arg(f),
-        EntryPoint::Module(m) => run_cmd.arg("python").arg("-m").arg(m),
-        EntryPoint::Command(cmd) => run_cmd.arg(cmd),
+/// `uv python install` then `uv sync`; `reinstall` also rebuilds every installed package.
+fn sync_env(
+    env: &UvEnv,
+    reinstall: bool,
+    log: &Logger,
+    exec: &mut impl FnMut(&mut Command, Sink) -> Result<ExitStatus>,
+) -> Result<()> {
+    let started = Instant::now();
+    run_with_retry(
+        || {
+            let mut install = env.cmd();
+            install.arg("python").arg("install");
+            if let Some(version) = read_python_version(env.proj)? {
+                install.arg(version);
+            }
+            exec(&mut install, Sink::Launcher)
+        },
+        5,
+        "uv python install",
+        log,
+    )?;
+
+    let locked = env.proj.join("uv.lock").exists();
+    run_with_retry(
+        || {
+            let mut sync = env.cmd();
+            sync.arg("sync");
+            if locked {
+                sync.arg("--frozen");
+            }
+            if reinstall {
+                sync.arg("--reinstall");
+            }
+            exec(&mut sync, Sink::Launcher)
+        },
+        5,
+        "uv sync",
+        log,
+    )?;
+
+    cleanup_uv_cache(env.runtime)?;
+    log.info(&format!(
+        "bootstrap finished in {:.1}s",
+        started.elapsed().as_secs_f64()
+    ));
+    Ok(())
+}
+
+/// Python run by the health probe, given the entry point's kind and target. It imports what
+/// the app would: the module, the console script's entry point, or a script's top-level
+/// imports (the script itself is not run).
+const PROBE_SCRIPT: &str = r#"import ast, importlib, importlib.metadata, os, sys
+kind, target = sys.argv[1:3]
+if kind == "module":
+    importlib.import_module(target)
+elif kind == "command":
+    for ep in importlib.metadata.entry_points(group="console_scripts", name=target):
+        ep.load()
+else:
+    sys.path.insert(0, os.path.dirname(os.path.abspath(target)))
+    with open(target, encoding="utf-8") as f:
+        tree = ast.parse(f.read())
+    for node in tree.body:
+        if isinstance(node, ast.Import):
+            for alias in node.names:
+                importlib.import_module(alias.name)
+        elif isinstance(node, ast.ImportFrom) and node.module and not node.level:
+            importlib.import_module(node.module)
+"#;
+
+/// True when the interpreter and, for a command, its executable are in the venv; checked on
+/// every launch, so it starts no process.
+fn venv_intact(venv: &Path, entry: &EntryPoint) -> bool {
+    let scripts = venv_scripts_dir(venv);
+    let program = match entry {
+        EntryPoint::Command(cmd) => venv_exe(&scripts, cmd),
+        _ => venv_exe(&scripts, "python"),
     };
+    venv_exe(&scripts, "python").exists() && program.exists()
+}
+
+/// True when the venv is intact and the probe can import the entry point.
+fn probe_env(
+    env: &UvEnv,
+    entry: &EntryPoint,
+    exec: &mut impl FnMut(&mut Command, Sink) -> Result<ExitStatus>,
+) -> bool {
+    if !venv_intact(env.venv, entry) {
+        return false;
+    }
+    let python = venv_exe(&venv_scripts_dir(env.venv), "python");
+    let (kind, target) = match entry {
+        EntryPoint::PythonFile(file) => ("python_file", file),
+        EntryPoint::Module(module) => ("module", module),
+        EntryPoint::Command(cmd) => ("command", cmd),
+    };
+    let mut probe = base_cmd(&python, env.proj, env.app_env);
+    probe.arg("-c").arg(PROBE_SCRIPT).arg(kind).arg(target);
+    matches!(exec(&mut probe, Sink::Launcher), Ok(status) if status.success())
+}
+
+/// Recreates a venv that is broken despite matching its fingerprint, once; failing if the
+/// probe still fails afterwards.
+fn repair_env(
+    env: &UvEnv,
+    entry: &EntryPoint,
+    log: &Logger,
+    exec: &mut impl FnMut(&mut Command, Sink) -> Result<ExitStatus>,
+) -> Result<()> {
+    remove_venv(env.venv)?;
+    sync_env(env, true, log, exec).context(Failure::Bootstrap)?;
+    if !probe_env(env, entry, exec) {
+        return Err(anyhow!(
+            "the environment in {} is still broken after it was recreated",
+            env.venv.display()
+        ))
+        .context(Failure::Bootstrap);
+    }
+    Ok(())
+}
 
-    let status = exec(&mut run_cmd)?;
-    if !status.success() {
-        bail!("entrypoint failed (exit {:?})", status.code());
+/// Takes the instance lock of every entry point that runs from `project` except `skip`,
+/// refusing while one of them is running: deleting the venv under it would break the app.
+/// `doing` names the deletion in that error, e.g. "resetting".
+fn lock_instances(
+    st: &State,
+    project: &str,
+    skip: &[Option<&str>],
+    doing: &str,
+) -> Result<Vec<instance::Guard>> {
+    let mut names = Vec::new();
+    if project == state::MAIN_PROJECT {
+        names.push(None);
     }
+    let named = st.entry_points.iter().filter(|named| named.project == project);
+    names.extend(named.map(|named| Some(named.name.as_str())));
+    let mut guards = Vec::new();
+    for name in names.into_iter().filter(|name| !skip.contains(name)) {
+        match instance::lock(name)? {
+            Some(guard) => guards.push(guard),
+            None => match name {
+                Some(name) => bail!(
+                    "entry point {name:?} is running; close it before {doing} its environment"
+                ),
+                None => bail!("the app is running; close it before {doing} its environment"),
+            },
+        }
+    }
+    Ok(guards)
+}
 
+fn remove_venv(venv: &Path) -> Result<()> {
+    if venv.exists() {
+        fs::remove_dir_all(venv).with_context(|| format!("remove {}", venv.display()))?;
+    }
     Ok(())
 }
 
+fn venv_scripts_dir(venv: &Path) -> PathBuf {
+    venv.join(if cfg!(windows) { "Scripts" } else { "bin" })
+}
+
+fn venv_exe(scripts: &Path, name: &str) -> PathBuf {
+    scripts.join(format!("{name}{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// The launcher's own arguments with reserved `--uvessel-*` flags removed.
+pub fn forwarded_args(args: &[OsString]) -> Vec<&OsStr> {
+    args.iter()
+        .map(OsString::as_os_str)
+        .filter(|arg| {
+            !arg.to_str()
+                .map(|a| a.starts_with(RESERVED_PREFIX))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
 fn ensure_runtime_dirs(runtime: &Path) -> Result<()> {
     for d in ["cache", "python", "python-bin", "tools", "tool-bin", "venv", "logs"] {
         fs::create_dir_all(runtime.join(d))?;
@@ -90,8 +451,8 @@ fn ensure_runtime_dirs(runtime: &Path) -> Result<()> {
     Ok(())
 }
 
-fn resolve_project(root: &Path, st: &State) -> Result<PathBuf> {
-    let proj = root.join(&st.project_rel);
+fn resolve_project(root: &Path, st: &State, name: &str) -> Result<PathBuf> {
+    let proj = root.join(&st.project(name)?.rel);
     let pyproject = proj.join("pyproject.toml");
     if !pyproject.exists() {
         bail!(
@@ -102,38 +463,99 @@ fn resolve_project(root: &Path, st: &State) -> Result<PathBuf> {
     Ok(proj)
 }
 
-fn build_uv_cmd(uv: &Path, proj: &Path, runtime: &Path) -> Command {
-    let mut c = Command::new(uv);
+fn build_uv_cmd(
+    uv: &Path,
+    proj: &Path,
+    runtime: &Path,
+    venv: &Path,
+    app_env: &[(String, String)],
+) -> Command {
+    let mut c = base_cmd(uv, proj, app_env);
+    c.envs(uv_env_pairs(runtime, venv));
+    c
+}
+
+/// The entry point run with the venv's own interpreter or script, set up the way `uv run`
+/// would; `None` when that executable is missing.
+fn direct_cmd(
+    venv: &Path,
+    proj: &Path,
+    entry: &EntryPoint,
+    app_env: &[(String, String)],
+) -> Option<Command> {
+    let scripts = venv_scripts_dir(venv);
+    let exe = |name: &str| venv_exe(&scripts, name);
+    let (program, prefix): (PathBuf, Vec<&str>) = match entry {
+        EntryPoint::PythonFile(f) => (exe("python"), vec![f]),
+        EntryPoint::Module(m) => (exe("python"), vec!["-m", m]),
+        EntryPoint::Command(cmd) => (exe(cmd), Vec::new()),
+    };
+    if !program.exists() {
+        return None;
+    }
+    let inherited = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(
+        std::iter::once(scripts.clone()).chain(std::env::split_paths(&inherited)),
+    )
+    .ok()?;
+    let mut c = base_cmd(&program, proj, app_env);
+    c.env("VIRTUAL_ENV", venv).env("PATH", path).args(prefix);
+    Some(c)
+}
+
+/// An interactive Python in the app's environment, attached to the user's terminal.
+fn shell_cmd(env: &UvEnv) -> Command {
+    let python = EntryPoint::Command("python".to_string());
+    let mut c = direct_cmd(env.venv, env.proj, &python, env.app_env).unwrap_or_else(|| {
+        let mut c = env.cmd();
+        c.arg("run").arg("python");
+        c
+    });
+    c.stdin(Stdio::inherit());
+    #[cfg(windows)]
+    if !CONSOLE {
+        // The windowed launcher has no terminal of its own when started from Explorer.
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+        c.creation_flags(supervise::creation_flags(CREATE_NEW_CONSOLE));
+    }
+    c
+}
+
+/// A child process with the isolated environment, the `UVESSEL_*` contract and no window.
+fn base_cmd(program: &Path, proj: &Path, app_env: &[(String, String)]) -> Command {
+    let mut c = Command::new(program);
+    for key in env_policy::stripped_vars(std::env::vars_os().map(|(key, _)| key)) {
+        c.env_remove(key);
+    }
     c.current_dir(proj)
-        .envs(uv_env_pairs(runtime))
+        .envs(app_env.iter().cloned())
         .stdin(Stdio::null());
     #[cfg(windows)]
     {
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
-        c.creation_flags(CREATE_NO_WINDOW);
+        let window = if CONSOLE { 0 } else { CREATE_NO_WINDOW };
+        c.creation_flags(supervise::creation_flags(window));
     }
     c
 }
 
-fn uv_env_pairs(runtime: &Path) -> Vec<(String, String)> {
+fn uv_env_pairs(runtime: &Path, venv: &Path) -> Vec<(String, String)> {
     vec![
         ("UV_CACHE_DIR".to_string(), runtime.join("cache").to_string_lossy().to_string()),
         ("UV_PYTHON_INSTALL_DIR".to_string(), runtime.join("python").to_string_lossy().to_string()),
         ("UV_PYTHON_BIN_DIR".to_string(), runtime.join("python-bin").to_string_lossy().to_string()),
-        ("UV_PROJECT_ENVIRONMENT".to_string(), runtime.join("venv").to_string_lossy().to_string()),
+        ("UV_PROJECT_ENVIRONMENT".to_string(), venv.to_string_lossy().to_string()),
         ("UV_TOOL_DIR".to_string(), runtime.join("tools").to_string_lossy().to_string()),
         ("UV_TOOL_BIN_DIR".to_string(), runtime.join("tool-bin").to_string_lossy().to_string()),
         ("UV_NO_CONFIG".to_string(), "1".to_string()),
     ]
 }
 
-fn needs_bootstrap(runtime: &Path, lock_mtime: u64, state_lock_mtime: u64) -> bool {
-    let venv_cfg = runtime.join("venv").join("pyvenv.cfg");
-    if !venv_cfg.exists() {
-        return true;
-    }
-    lock_mtime != 0 && lock_mtime != state_lock_mtime
+/// True when the venv is missing or any input it was synced from has changed.
+fn needs_bootstrap(venv: &Path, current: &state::Fingerprint, recorded: &state::Fingerprint) -> bool {
+    !venv.join("pyvenv.cfg").exists() || current != recorded
 }
 
 fn read_python_version(proj: &Path) -> Result<Option<String>> {
@@ -163,6 +585,7 @@ fn run_with_retry(
     mut make_status: impl FnMut() -> Result<ExitStatus>,
     attempts: usize,
     label: &str,
+    log: &Logger,
 ) -> Result<()> {
     let mut delay = std::time::Duration::from_millis(250);
     for i in 0..attempts {
@@ -171,13 +594,14 @@ fn run_with_retry(
             return Ok(());
         }
         if i + 1 == attempts {
-            eprintln!("warning: {label} failed after {attempts} attempts");
+            log.error(&format!("{label} failed after {attempts} attempts"));
             bail!("{label} failed (exit {:?})", status.code());
         }
-        eprintln!(
-            "warning: {label} failed (exit {:?}), retrying...",
-            status.code()
-        );
+        log.info(&format!(
+            "{label} failed (exit {:?}), retrying in {}ms...",
+            status.code(),
+            delay.as_millis()
+        ));
         std::thread::sleep(delay);
         delay = std::cmp::min(delay * 2, std::time::Duration::from_secs(5));
     }
@@ -193,24 +617,37 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let runtime = tmp.path().join(".runtime");
         fs::create_dir_all(&runtime).unwrap();
-        assert!(needs_bootstrap(&runtime, 0, 0));
+        let fingerprint = state::Fingerprint::default();
+        assert!(needs_bootstrap(&runtime.join("venv"), &fingerprint, &fingerprint));
     }
 
     #[test]
-    fn needs_bootstrap_when_lock_changed() {
+    fn needs_bootstrap_when_project_content_changed() {
         let tmp = tempfile::tempdir().unwrap();
-        let runtime = tmp.path().join(".runtime");
-        fs::create_dir_all(runtime.join("venv")).unwrap();
-        fs::write(runtime.join("venv").join("pyvenv.cfg"), "cfg").unwrap();
-        assert!(needs_bootstrap(&runtime, 10, 5));
-        assert!(!needs_bootstrap(&runtime, 10, 10));
+        let proj = tmp.path().join("app");
+        let venv = tmp.path().join(".runtime").join("venv");
+        fs::create_dir_all(&proj).unwrap();
+        fs::create_dir_all(&venv).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "home = x\nversion_info = 3.12.4\n").unwrap();
+        fs::write(proj.join("uv.lock"), "lock v1").unwrap();
+        let recorded = state::Fingerprint::read(&proj, &venv).unwrap();
+        assert_eq!(recorded.interpreter, "3.12.4");
+
+        // Rewriting identical content only bumps the mtime.
+        fs::write(proj.join("uv.lock"), "lock v1").unwrap();
+        let current = state::Fingerprint::read(&proj, &venv).unwrap();
+        assert!(!needs_bootstrap(&venv, &current, &recorded));
+
+        fs::write(proj.join(".python-version"), "3.13\n").unwrap();
+        let current = state::Fingerprint::read(&proj, &venv).unwrap();
+        assert!(needs_bootstrap(&venv, &current, &recorded));
     }
 
     #[test]
     fn uv_env_pairs_include_no_config() {
         let tmp = tempfile::tempdir().unwrap();
         let runtime = tmp.path().join(".runtime");
-        let envs = uv_env_pairs(&runtime);
+        let envs = uv_env_pairs(&runtime, &runtime.join("venv"));
         assert!(envs.iter().any(|(k, v)| k == "UV_NO_CONFIG" && v == "1"));
     }
 
@@ -222,4 +659,302 @@ mod tests {
         let v = read_python_version(proj).unwrap();
         assert_eq!(v.as_deref(), Some("3.12"));
     }
+
+    #[test]
+    fn forwarded_args_drops_reserved_flags() {
+        let args: Vec<OsString> = ["--uvessel-info", "report.txt", "--uvessel-shell", "-v"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        assert_eq!(forwarded_args(&args), vec![OsStr::new("report.txt"), OsStr::new("-v")]);
+    }
+
+    #[test]
+    fn run_appends_static_then_forwarded_args() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        let st = installed_fixture(root, EntryPoint::Module("app.cli".to_string()));
+        state::write_state(
+            &state::state_path(root),
+            &State {
+                entry_args: vec!["--profile".to_string(), "v{version}".to_string()],
+                ..st
+            },
+        )
+        .unwrap();
+
+        let args = vec![
+            OsString::from("C:\\My Files\\a b.txt"),
+            OsString::from("--uvessel-trace"),
+        ];
+        let mut seen = Vec::new();
+        run_with_executor(root, &log, None, &args, Some(Path::new("app.sock")), |cmd, sink| {
+            if sink == Sink::Launcher {
+                return Ok(success_status());
+            }
+            let var = |key: &str| cmd.get_envs().find(|(k, _)| *k == key).and_then(|(_, v)| v);
+            assert_eq!(var("UVESSEL_ROOT"), Some(root.as_os_str()));
+            assert_eq!(var("UVESSEL_INSTANCE_SOCKET"), Some(OsStr::new("app.sock")));
+            seen = cmd.get_args().map(|a| a.to_os_string()).collect();
+            Ok(success_status())
+        })
+        .unwrap();
+
+        let seen: Vec<&OsStr> = seen.iter().map(OsString::as_os_str).collect();
+        assert_eq!(
+            seen,
+            vec!["-m", "app.cli", "--profile", "v1.0.0", "C:\\My Files\\a b.txt"]
+        );
+    }
+
+    #[test]
+    fn run_uses_venv_interpreter_unless_just_synced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        installed_fixture(root, EntryPoint::PythonFile("main.py".to_string()));
+        let python = venv_python(&root.join(".runtime").join("venv"));
+
+        let mut programs = Vec::new();
+        let run = |programs: &mut Vec<PathBuf>| {
+            run_with_executor(root, &log, None, &[], None, |cmd, sink| {
+                if sink != Sink::Launcher {
+                    programs.push(PathBuf::from(cmd.get_program()));
+                }
+                Ok(success_status())
+            })
+            .unwrap();
+        };
+        run(&mut programs);
+        fs::write(root.join("app").join("uv.lock"), "lock v2").unwrap();
+        run(&mut programs);
+
+        assert_eq!(programs, vec![python, root.join("uv.exe")]);
+    }
+
+    #[test]
+    fn broken_venv_is_recreated_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        installed_fixture(root, EntryPoint::Module("app.cli".to_string()));
+        let python = venv_python(&root.join(".runtime").join("venv"));
+
+        // The app fails and so does the probe that follows; the repair sync recreates the
+        // interpreter and the app is started again.
+        let mut probes = 0;
+        let mut runs = 0;
+        let mut syncs = Vec::new();
+        let code = run_with_executor(root, &log, None, &[], None, |cmd, sink| {
+            let args: Vec<String> = cmd
+                .get_args()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect();
+            if sink != Sink::Launcher {
+                runs += 1;
+                return Ok(if runs == 1 { failure_status() } else { success_status() });
+            }
+            if args.first().map(String::as_str) == Some("-c") {
+                probes += 1;
+                assert_eq!(&args[2..], ["module", "app.cli"]);
+                return Ok(if probes == 1 { failure_status() } else { success_status() });
+            }
+            if args.first().map(String::as_str) == Some("sync") {
+                fs::create_dir_all(python.parent().unwrap()).unwrap();
+                fs::write(&python, b"").unwrap();
+                syncs.push(args);
+            }
+            Ok(success_status())
+        })
+        .unwrap();
+        assert_eq!(code, 0);
+        assert_eq!((runs, probes), (2, 2));
+        assert_eq!(syncs, vec![vec!["sync".to_string(), "--reinstall".to_string()]]);
+
+        // A venv that is still broken after the repair is reported as a bootstrap failure.
+        installed_fixture(root, EntryPoint::Module("app.cli".to_string()));
+        let err = run_with_executor(root, &log, None, &[], None, |cmd, sink| {
+            let probe = cmd.get_args().next() == Some(OsStr::new("-c"));
+            Ok(if probe || sink != Sink::Launcher { failure_status() } else { success_status() })
+        })
+        .unwrap_err();
+        assert_eq!(exit_code::for_error(&err), exit_code::TEMP_FAILURE);
+    }
+
+    #[test]
+    fn repair_is_refused_while_another_entry_point_uses_the_venv() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        let mut st = installed_fixture(root, EntryPoint::Module("app.cli".to_string()));
+        let name = format!("probe-{}", std::process::id());
+        st.entry_points.push(state::NamedEntry {
+            name: name.clone(),
+            entry: EntryPoint::Module("app.probe".to_string()),
+            args: Vec::new(),
+            project: state::MAIN_PROJECT.to_string(),
+        });
+        state::write_state(&state::state_path(root), &st).unwrap();
+        let python = venv_python(&root.join(".runtime").join("venv"));
+
+        let _running = instance::mark_running(Some(&name)).unwrap().unwrap();
+        let err = run_with_executor(root, &log, None, &[], None, |cmd, sink| {
+            let probe = cmd.get_args().next() == Some(OsStr::new("-c"));
+            Ok(if probe || sink != Sink::Launcher { failure_status() } else { success_status() })
+        })
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("is running"), "{err:#}");
+        assert!(python.exists());
+    }
+
+    #[test]
+    fn maintenance_flags_sync_without_running_the_app() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        installed_fixture(root, EntryPoint::PythonFile("main.py".to_string()));
+        let python = venv_python(&root.join(".runtime").join("venv"));
+
+        for flag in ["--uvessel-sync", "--uvessel-reset-env"] {
+            let args = [OsString::from(flag)];
+            let mut sinks = Vec::new();
+            let code = run_with_executor(root, &log, None, &args, None, |_, sink| {
+                sinks.push(sink);
+                Ok(success_status())
+            })
+            .unwrap();
+            assert_eq!(code, 0);
+            assert_eq!(sinks, vec![Sink::Launcher, Sink::Launcher], "{flag}");
+        }
+        // Only the reset deletes the venv before syncing it.
+        assert!(!python.exists());
+    }
+
+    #[test]
+    fn named_entry_runs_in_its_project_venv() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        let mut st = installed_fixture(root, EntryPoint::PythonFile("main.py".to_string()));
+        let tool = root.join("tool");
+        fs::create_dir_all(&tool).unwrap();
+        fs::write(tool.join("pyproject.toml"), "[project]\nname = \"tool\"\n").unwrap();
+        st.projects.push(state::Project {
+            name: "tool".to_string(),
+            rel: "tool".to_string(),
+            fingerprint: state::Fingerprint::default(),
+        });
+        st.entry_points.push(state::NamedEntry {
+            name: "worker".to_string(),
+            entry: EntryPoint::Module("tool.worker".to_string()),
+            args: Vec::new(),
+            project: "tool".to_string(),
+        });
+        state::write_state(&state::state_path(root), &st).unwrap();
+
+        // The tool venv does not exist yet, so it is bootstrapped before the run.
+        let mut seen = Vec::new();
+        run_with_executor(root, &log, Some("worker"), &[], None, |cmd, _| {
+            let venv = cmd
+                .get_envs()
+                .find(|(k, _)| *k == "UV_PROJECT_ENVIRONMENT")
+                .and_then(|(_, v)| v.map(PathBuf::from));
+            seen.push((cmd.get_current_dir().map(Path::to_path_buf), venv));
+            Ok(success_status())
+        })
+        .unwrap();
+
+        let expected = (Some(tool), Some(root.join(".runtime").join("venv-tool")));
+        assert_eq!(seen.len(), 3);
+        assert!(seen.iter().all(|s| *s == expected));
+    }
+
+    #[test]
+    fn failed_app_run_leaves_a_crash_record() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let log = crate::logging::init(root).unwrap();
+        installed_fixture(root, EntryPoint::PythonFile("main.py".to_string()));
+
+        let code = run_with_executor(root, &log, None, &[], None, |cmd, sink| {
+            if sink == Sink::Launcher {
+                return Ok(success_status());
+            }
+            let faulthandler = cmd.get_envs().any(|(k, v)| {
+                k == "PYTHONFAULTHANDLER" && v == Some(OsStr::new("1"))
+            });
+            assert!(faulthandler);
+            Ok(exit_status(3))
+        })
+        .unwrap();
+
+        assert_eq!(code, 3);
+        let records: Vec<_> = fs::read_dir(crash::crashes_dir(root)).unwrap().collect();
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn run_reports_missing_uv_as_reserved_code() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = crate::logging::init(tmp.path()).unwrap();
+        let err = run_with_executor(tmp.path(), &log, None, &[], None, |_, _| Ok(success_status()))
+            .unwrap_err();
+        assert_eq!(exit_code::for_error(&err), exit_code::UNAVAILABLE);
+    }
+
+    /// Lays out an installed root whose venv is already synced, so no bootstrap runs.
+    fn installed_fixture(root: &Path, entry: EntryPoint) -> State {
+        fs::write(root.join("uv.exe"), b"").unwrap();
+        let proj = root.join("app");
+        fs::create_dir_all(&proj).unwrap();
+        fs::write(proj.join("pyproject.toml"), "[project]\nname = \"app\"\n").unwrap();
+        let venv = root.join(".runtime").join("venv");
+        fs::create_dir_all(&venv).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "cfg").unwrap();
+        let python = venv_python(&venv);
+        fs::create_dir_all(python.parent().unwrap()).unwrap();
+        fs::write(&python, b"").unwrap();
+        let st = State {
+            projects: vec![state::Project {
+                name: state::MAIN_PROJECT.to_string(),
+                rel: "app".to_string(),
+                fingerprint: state::Fingerprint::read(&proj, &venv).unwrap(),
+            }],
+            entry,
+            installed: true,
+            launcher_version: "1.0.0".to_string(),
+            entry_args: Vec::new(),
+            entry_points: Vec::new(),
+            shortcuts: Vec::new(),
+        };
+        state::write_state(&state::state_path(root), &st).unwrap();
+        st
+    }
+
+    fn venv_python(venv: &Path) -> PathBuf {
+        let scripts = venv.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        scripts.join(format!("python{}", std::env::consts::EXE_SUFFIX))
+    }
+
+    fn success_status() -> ExitStatus {
+        exit_status(0)
+    }
+
+    fn failure_status() -> ExitStatus {
+        exit_status(1)
+    }
+
+    fn exit_status(code: u32) -> ExitStatus {
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::ExitStatusExt;
+            ExitStatus::from_raw(code)
+        }
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            ExitStatus::from_raw((code << 8) as i32)
+        }
+    }
 }
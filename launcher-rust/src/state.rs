@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
     fs,
     path::{Path, PathBuf},
-    time::{Duration, UNIX_EPOCH},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -17,27 +17,179 @@ pub enum EntryPoint {
     Command(String),
 }
 
+/// Name of the project that runs the default entry point.
+pub const MAIN_PROJECT: &str = "main";
+
+/// An additional entry point from `[entry_points.<name>]`, launched by the `<name>.exe` shim.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamedEntry {
+    pub name: String,
+    pub entry: EntryPoint,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "main_project")]
+    pub project: String,
+}
+
+/// A uv project under `app/`, synced into its own venv (see `venv_dir`).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Project {
+    pub name: String,
+    pub rel: String,
+    #[serde(default)]
+    pub fingerprint: Fingerprint,
+}
+
+/// What a project's venv was synced from; the launcher syncs again when any of it differs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// sha256 of `uv.lock`, `pyproject.toml` and `.python-version`; empty when absent.
+    pub uv_lock: String,
+    pub pyproject: String,
+    pub python_version: String,
+    /// Interpreter version from the venv's pyvenv.cfg.
+    pub interpreter: String,
+}
+
+impl Fingerprint {
+    pub fn read(proj: &Path, venv: &Path) -> Result<Self> {
+        Ok(Self {
+            uv_lock: file_sha256(&proj.join("uv.lock"))?,
+            pyproject: file_sha256(&proj.join("pyproject.toml"))?,
+            python_version: file_sha256(&proj.join(".python-version"))?,
+            interpreter: venv_interpreter(venv)?,
+        })
+    }
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    Ok(Sha256::digest(&bytes).iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// `version_info` (written by uv) or `version` (written by the venv module) from pyvenv.cfg.
+fn venv_interpreter(venv: &Path) -> Result<String> {
+    let cfg = venv.join("pyvenv.cfg");
+    if !cfg.exists() {
+        return Ok(String::new());
+    }
+    let contents = fs::read_to_string(&cfg).with_context(|| format!("read {}", cfg.display()))?;
+    let mut version = String::new();
+    for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+        match key.trim() {
+            "version_info" => return Ok(value.trim().to_string()),
+            "version" => version = value.trim().to_string(),
+            _ => {}
+        }
+    }
+    Ok(version)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "StateFile")]
 pub struct State {
-    pub project_rel: String,
+    pub projects: Vec<Project>,
     pub entry: EntryPoint,
-    pub lock_mtime_unix: u64,
     pub installed: bool,
     #[serde(default)]
     pub launcher_version: String,
+    /// Static arguments from config.toml, passed before any forwarded from the command line.
+    #[serde(default)]
+    pub entry_args: Vec<String>,
+    #[serde(default)]
+    pub entry_points: Vec<NamedEntry>,
+    /// Start menu shortcuts the installer created; kept when the launcher rewrites the file.
+    #[serde(default)]
+    pub shortcuts: Vec<String>,
+}
+
+/// On-disk form of `State`, which also accepts the single-project `project_rel` layout.
+#[derive(Deserialize)]
+struct StateFile {
+    #[serde(default)]
+    projects: Vec<Project>,
+    #[serde(default)]
+    project_rel: Option<String>,
+    entry: EntryPoint,
+    installed: bool,
+    #[serde(default)]
+    launcher_version: String,
+    #[serde(default)]
+    entry_args: Vec<String>,
+    #[serde(default)]
+    entry_points: Vec<NamedEntry>,
+    #[serde(default)]
+    shortcuts: Vec<String>,
+}
+
+impl From<StateFile> for State {
+    fn from(file: StateFile) -> Self {
+        let mut projects = file.projects;
+        if let (true, Some(rel)) = (projects.is_empty(), file.project_rel) {
+            projects.push(Project {
+                name: MAIN_PROJECT.to_string(),
+                rel,
+                fingerprint: Fingerprint::default(),
+            });
+        }
+        State {
+            projects,
+            entry: file.entry,
+            installed: file.installed,
+            launcher_version: file.launcher_version,
+            entry_args: file.entry_args,
+            entry_points: file.entry_points,
+            shortcuts: file.shortcuts,
+        }
+    }
+}
+
+fn main_project() -> String {
+    MAIN_PROJECT.to_string()
+}
+
+impl State {
+    /// The entry point, static args and project for a shim: the default entry when `name` is `None`.
+    pub fn entry_for(&self, name: Option<&str>) -> Result<(&EntryPoint, &[String], &str)> {
+        let Some(name) = name else {
+            return Ok((&self.entry, &self.entry_args, MAIN_PROJECT));
+        };
+        match self.entry_points.iter().find(|named| named.name == name) {
+            Some(named) => Ok((&named.entry, &named.args, &named.project)),
+            None => bail!("entry point {name:?} not found in state.json"),
+        }
+    }
+
+    pub fn project(&self, name: &str) -> Result<&Project> {
+        match self.projects.iter().find(|p| p.name == name) {
+            Some(project) => Ok(project),
+            None => bail!("project {name:?} not found in state.json"),
+        }
+    }
+
+    pub fn project_mut(&mut self, name: &str) -> Result<&mut Project> {
+        match self.projects.iter_mut().find(|p| p.name == name) {
+            Some(project) => Ok(project),
+            None => bail!("project {name:?} not found in state.json"),
+        }
+    }
 }
 
 pub fn state_path(root: &Path) -> PathBuf {
     root.join(".runtime").join("state.json")
 }
 
-pub fn file_mtime_unix(path: &Path) -> Result<u64> {
-    let meta = fs::metadata(path).with_context(|| format!("metadata {}", path.display()))?;
-    let mtime = meta.modified().context("modified time")?;
-    Ok(mtime
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs())
+/// The venv a project syncs into: `.runtime/venv` for the main project,
+/// `.runtime/venv-<name>` for the others.
+pub fn venv_dir(runtime: &Path, project: &str) -> PathBuf {
+    if project == MAIN_PROJECT {
+        runtime.join("venv")
+    } else {
+        runtime.join(format!("venv-{project}"))
+    }
 }
 
 pub fn read_state(state_path: &Path) -> Result<State> {
@@ -61,14 +213,73 @@ mod tests {
     #[test]
     fn serde_roundtrip_state() {
         let state = State {
-            project_rel: "app\\proj".to_string(),
+            projects: vec![Project {
+                name: MAIN_PROJECT.to_string(),
+                rel: "app\\proj".to_string(),
+                fingerprint: Fingerprint {
+                    uv_lock: "ab12".to_string(),
+                    interpreter: "3.12.4".to_string(),
+                    ..Fingerprint::default()
+                },
+            }],
             entry: EntryPoint::PythonFile("main.py".to_string()),
-            lock_mtime_unix: 123,
             installed: true,
             launcher_version: "1.2.3".to_string(),
+            entry_args: vec!["--verbose".to_string()],
+            entry_points: vec![NamedEntry {
+                name: "cli".to_string(),
+                entry: EntryPoint::Module("app.cli".to_string()),
+                args: Vec::new(),
+                project: MAIN_PROJECT.to_string(),
+            }],
+            shortcuts: vec!["My App".to_string(), "My App cli".to_string()],
         };
         let s = serde_json::to_string(&state).unwrap();
         let out: State = serde_json::from_str(&s).unwrap();
         assert_eq!(state, out);
     }
+
+    #[test]
+    fn entry_for_looks_up_named_entries() {
+        let state = State {
+            projects: vec![Project {
+                name: MAIN_PROJECT.to_string(),
+                rel: "app".to_string(),
+                fingerprint: Fingerprint::default(),
+            }],
+            entry: EntryPoint::PythonFile("main.py".to_string()),
+            installed: true,
+            launcher_version: String::new(),
+            entry_args: Vec::new(),
+            entry_points: vec![NamedEntry {
+                name: "server".to_string(),
+                entry: EntryPoint::Module("app.server".to_string()),
+                args: vec!["--port".to_string(), "8080".to_string()],
+                project: "backend".to_string(),
+            }],
+            shortcuts: Vec::new(),
+        };
+        let (entry, _, project) = state.entry_for(None).unwrap();
+        assert_eq!(entry, &EntryPoint::PythonFile("main.py".to_string()));
+        assert_eq!(project, MAIN_PROJECT);
+        let (entry, args, project) = state.entry_for(Some("server")).unwrap();
+        assert_eq!(entry, &EntryPoint::Module("app.server".to_string()));
+        assert_eq!(args, ["--port", "8080"]);
+        assert_eq!(project, "backend");
+        assert!(state.entry_for(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn legacy_single_project_state_migrates() {
+        let legacy = r#"{
+            "project_rel": "app\\proj",
+            "entry": {"kind": "python_file", "value": "main.py"},
+            "lock_mtime_unix": 42,
+            "installed": true
+        }"#;
+        let state: State = serde_json::from_str(legacy).unwrap();
+        let main = state.project(MAIN_PROJECT).unwrap();
+        assert_eq!(main.rel, "app\\proj");
+        assert_eq!(main.fingerprint, Fingerprint::default());
+    }
 }
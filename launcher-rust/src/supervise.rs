@@ -0,0 +1,369 @@
+//! Supervision of the app: the `[restart]` policy from config.toml decides whether an exited
+//! app is started again, and termination signals sent to the launcher reach the app and stop
+//! further restarts.
+
+use std::{
+    collections::VecDeque,
+    process::{Child, Command},
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::config;
+
+/// Set once the launcher was asked to terminate; no restart follows.
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Pid of the running child, 0 when there is none.
+static CHILD: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    Never,
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub restart: Restart,
+    /// Restarts allowed over the launcher's lifetime; 0 means no limit.
+    pub max_restarts: usize,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    /// The launcher gives up once this many restarts fall within `crash_loop_window`; 0
+    /// turns the check off.
+    pub crash_loop_restarts: usize,
+    pub crash_loop_window: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_config() -> Self {
+        let restart = match config::RESTART_POLICY {
+            "always" => Restart::Always,
+            "on-failure" => Restart::OnFailure,
+            _ => Restart::Never,
+        };
+        Self {
+            restart,
+            max_restarts: config::RESTART_MAX,
+            backoff: Duration::from_millis(config::RESTART_BACKOFF_MS),
+            max_backoff: Duration::from_millis(config::RESTART_MAX_BACKOFF_MS),
+            crash_loop_restarts: config::CRASH_LOOP_RESTARTS,
+            crash_loop_window: Duration::from_secs(config::CRASH_LOOP_WINDOW_SECS),
+        }
+    }
+}
+
+/// What to do after the app exited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Exit,
+    Restart { attempt: usize, delay: Duration },
+    /// A restart was due but a limit forbids it.
+    GiveUp(String),
+}
+
+/// Applies a `RestartPolicy` across the runs of one launch.
+pub struct Supervisor {
+    policy: RestartPolicy,
+    restarts: usize,
+    recent: VecDeque<Instant>,
+    delay: Duration,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            restarts: 0,
+            recent: VecDeque::new(),
+            delay: policy.backoff,
+        }
+    }
+
+    /// Decides on a restart after a run that lasted `ran_for` and ended with `code`.
+    pub fn after_exit(&mut self, code: i32, ran_for: Duration, now: Instant) -> Decision {
+        let wanted = match self.policy.restart {
+            Restart::Never => false,
+            Restart::OnFailure => code != 0,
+            Restart::Always => true,
+        };
+        if !wanted || stop_requested() {
+            return Decision::Exit;
+        }
+        let max = self.policy.max_restarts;
+        if max != 0 && self.restarts >= max {
+            return Decision::GiveUp(format!("reached the limit of {max} restarts"));
+        }
+        let window = self.policy.crash_loop_window;
+        while self.recent.front().is_some_and(|at| now.duration_since(*at) > window) {
+            self.recent.pop_front();
+        }
+        let loop_limit = self.policy.crash_loop_restarts;
+        if loop_limit != 0 && self.recent.len() >= loop_limit {
+            return Decision::GiveUp(format!(
+                "crash loop: {loop_limit} restarts within {}s",
+                window.as_secs()
+            ));
+        }
+        // A run that outlasted the window was healthy, so the backoff starts over.
+        if ran_for >= window {
+            self.delay = self.policy.backoff;
+        }
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(self.policy.max_backoff);
+        self.recent.push_back(now);
+        self.restarts += 1;
+        Decision::Restart {
+            attempt: self.restarts,
+            delay,
+        }
+    }
+}
+
+pub fn stop_requested() -> bool {
+    STOP.load(Ordering::SeqCst)
+}
+
+/// Sleeps for `delay` unless termination is requested first; returns false in that case.
+pub fn wait_unless_stopped(delay: Duration) -> bool {
+    let until = Instant::now() + delay;
+    while !stop_requested() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        std::thread::sleep(left.min(Duration::from_millis(100)));
+    }
+    false
+}
+
+/// Starts `cmd` and makes the child the target of forwarded signals until the guard drops.
+/// On Windows, which has no signal to forward, the child joins a job that ends it if the
+/// launcher is killed; built with `creation_flags`, it is resumed only once it is in the
+/// job, so nothing it starts, such as the app under `uv run`, escapes the job.
+pub fn spawn(cmd: &mut Command) -> std::io::Result<(Child, ChildGuard)> {
+    let child = cmd.spawn()?;
+    #[cfg(windows)]
+    if let Err(err) = job::start(&child) {
+        let mut child = child;
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(err);
+    }
+    CHILD.store(child.id(), Ordering::SeqCst);
+    Ok((child, ChildGuard))
+}
+
+/// Windows creation flags for a command run through `spawn`: `flags` plus
+/// `CREATE_SUSPENDED`.
+#[cfg(windows)]
+pub fn creation_flags(flags: u32) -> u32 {
+    flags | windows_sys::Win32::System::Threading::CREATE_SUSPENDED
+}
+
+/// Called when the launcher exits on its own: processes the app left behind keep running
+/// instead of going down with the job.
+pub fn release_children() {
+    #[cfg(windows)]
+    job::set_kill_on_close(false);
+}
+
+pub struct ChildGuard;
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        CHILD.store(0, Ordering::SeqCst);
+    }
+}
+
+/// On Unix, SIGTERM and SIGHUP are passed on to the running child; SIGINT is not, since a
+/// terminal's Ctrl+C already reaches the whole process group. On Windows the child shares
+/// the console and gets Ctrl+C and Ctrl+Break itself, so the launcher only waits for it;
+/// a launcher that is killed takes the child with it through the job in `spawn`.
+/// Either way the app is not restarted afterwards.
+pub fn forward_termination_signals() {
+    #[cfg(unix)]
+    unsafe {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for signal in [libc::SIGTERM, libc::SIGHUP, libc::SIGINT] {
+            libc::signal(signal, handler);
+        }
+    }
+    #[cfg(windows)]
+    unsafe {
+        windows_sys::Win32::System::Console::SetConsoleCtrlHandler(Some(on_ctrl), 1);
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only atomics and `kill`, which are safe inside a signal handler.
+    STOP.store(true, Ordering::SeqCst);
+    let pid = CHILD.load(Ordering::SeqCst);
+    if pid != 0 && signal != libc::SIGINT {
+        unsafe { libc::kill(pid as libc::pid_t, signal) };
+    }
+}
+
+/// A job with `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`: the launcher holds its only handle, so
+/// when Windows tears the launcher down, even via TerminateProcess, the app goes with it.
+#[cfg(windows)]
+mod job {
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::sync::OnceLock;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+
+    /// 0 when the job could not be created.
+    static JOB: OnceLock<isize> = OnceLock::new();
+
+    fn handle() -> isize {
+        *JOB.get_or_init(|| {
+            let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if job != 0 {
+                set_limits(job, true);
+            }
+            job
+        })
+    }
+
+    fn set_limits(job: isize, kill_on_close: bool) {
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        if kill_on_close {
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        }
+        unsafe {
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const _,
+                std::mem::size_of_val(&info) as u32,
+            )
+        };
+    }
+
+    /// Puts the suspended child into the job and lets it run. Joining is best effort: a child
+    /// outside the job still runs, it only outlives a killed launcher.
+    pub fn start(child: &Child) -> io::Result<()> {
+        let job = handle();
+        if job != 0 {
+            unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as isize) };
+        }
+        resume(child.id())
+    }
+
+    /// Resumes the threads of `pid`; a process created suspended has only its main thread.
+    fn resume(pid: u32) -> io::Result<()> {
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let mut entry: THREADENTRY32 = unsafe { std::mem::zeroed() };
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut result = Err(io::Error::new(io::ErrorKind::NotFound, "no thread to resume"));
+        let mut more = unsafe { Thread32First(snapshot, &mut entry) } != 0;
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                unsafe {
+                    let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                    if thread != 0 {
+                        if ResumeThread(thread) != u32::MAX {
+                            result = Ok(());
+                        }
+                        CloseHandle(thread);
+                    }
+                }
+            }
+            more = unsafe { Thread32Next(snapshot, &mut entry) } != 0;
+        }
+        unsafe { CloseHandle(snapshot) };
+        result
+    }
+
+    pub fn set_kill_on_close(kill_on_close: bool) {
+        if let Some(&job) = JOB.get().filter(|&&job| job != 0) {
+            set_limits(job, kill_on_close);
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn on_ctrl(ctrl: u32) -> windows_sys::Win32::Foundation::BOOL {
+    use windows_sys::Win32::System::Console::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+    STOP.store(true, Ordering::SeqCst);
+    // Handled: keep running until the child exits. Closing the console still ends us.
+    matches!(ctrl, CTRL_C_EVENT | CTRL_BREAK_EVENT) as _
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(restart: Restart) -> RestartPolicy {
+        RestartPolicy {
+            restart,
+            max_restarts: 0,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(4),
+            crash_loop_restarts: 3,
+            crash_loop_window: Duration::from_secs(60),
+        }
+    }
+
+    fn delay(decision: Decision) -> Duration {
+        match decision {
+            Decision::Restart { delay, .. } => delay,
+            other => panic!("expected a restart, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn policy_decides_which_exits_restart() {
+        let now = Instant::now();
+        let quick = Duration::from_secs(1);
+        let mut never = Supervisor::new(policy(Restart::Never));
+        assert_eq!(never.after_exit(1, quick, now), Decision::Exit);
+        let mut on_failure = Supervisor::new(policy(Restart::OnFailure));
+        assert_eq!(on_failure.after_exit(0, quick, now), Decision::Exit);
+        let restarted = on_failure.after_exit(1, quick, now);
+        assert!(matches!(restarted, Decision::Restart { attempt: 1, .. }));
+        let mut always = Supervisor::new(policy(Restart::Always));
+        assert!(matches!(always.after_exit(0, quick, now), Decision::Restart { .. }));
+
+        let mut limited = Supervisor::new(RestartPolicy {
+            max_restarts: 1,
+            ..policy(Restart::Always)
+        });
+        assert!(matches!(limited.after_exit(0, quick, now), Decision::Restart { .. }));
+        assert!(matches!(limited.after_exit(0, quick, now), Decision::GiveUp(_)));
+    }
+
+    #[test]
+    fn backoff_doubles_until_a_healthy_run_and_crash_loops_give_up() {
+        let start = Instant::now();
+        let quick = Duration::from_secs(1);
+        let mut sup = Supervisor::new(policy(Restart::OnFailure));
+        assert_eq!(delay(sup.after_exit(1, quick, start)), Duration::from_secs(1));
+        assert_eq!(delay(sup.after_exit(1, quick, start)), Duration::from_secs(2));
+        assert_eq!(delay(sup.after_exit(1, quick, start)), Duration::from_secs(4));
+        // Three restarts inside the window: the fourth is refused.
+        assert!(matches!(sup.after_exit(1, quick, start), Decision::GiveUp(_)));
+
+        // Long after, a run that outlasted the window restarts with the initial delay.
+        let later = start + Duration::from_secs(300);
+        let healthy = Duration::from_secs(120);
+        assert_eq!(delay(sup.after_exit(1, healthy, later)), Duration::from_secs(1));
+    }
+}
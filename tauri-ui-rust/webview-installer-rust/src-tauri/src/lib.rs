@@ -11,6 +11,15 @@ struct InstallUiInfo {
     mode: String,
     log_file: Option<String>,
     launch_file: Option<String>,
+    preflight_file: Option<String>,
+    close_file: Option<String>,
+    close_decision_file: Option<String>,
+    cancel_file: Option<String>,
+    progress_file: Option<String>,
+    diagnose_file: Option<String>,
+    diagnose_result_file: Option<String>,
+    error_file: Option<String>,
+    log_dir: Option<String>,
 }
 
 #[tauri::command]
@@ -33,7 +42,9 @@ fn get_install_status(state: tauri::State<'_, InstallUiInfo>) -> InstallStatus {
     let status = match std::fs::read_to_string(path) {
         Ok(contents) => {
             let lowered = contents.trim().to_lowercase();
-            if lowered.contains("fail") {
+            if lowered.contains("cancel") {
+                "cancelled"
+            } else if lowered.contains("fail") {
                 "fail"
             } else if lowered.contains("ok") || lowered.contains("done") {
                 "ok"
@@ -56,6 +67,126 @@ fn mark_launch_requested(state: tauri::State<'_, InstallUiInfo>) -> Result<(), S
     std::fs::write(path, "launch").map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn read_preflight_report(
+    state: tauri::State<'_, InstallUiInfo>,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(path) = state.preflight_file.as_ref() else {
+        return Ok(None);
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_close_status(
+    state: tauri::State<'_, InstallUiInfo>,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(path) = state.close_file.as_ref() else {
+        return Ok(None);
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn resolve_close_prompt(force: bool, state: tauri::State<'_, InstallUiInfo>) -> Result<(), String> {
+    let Some(path) = state.close_decision_file.as_ref() else {
+        return Ok(());
+    };
+    let decision = if force { "force" } else { "cancel" };
+    std::fs::write(path, decision).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_install_progress(
+    state: tauri::State<'_, InstallUiInfo>,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(path) = state.progress_file.as_ref() else {
+        return Ok(None);
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    // The installer rewrites the file on every event; a torn read just waits for the next poll.
+    Ok(serde_json::from_str(&contents).ok())
+}
+
+#[tauri::command]
+fn request_cancel(state: tauri::State<'_, InstallUiInfo>) -> Result<(), String> {
+    let Some(path) = state.cancel_file.as_ref() else {
+        return Ok(());
+    };
+    std::fs::write(path, "cancel").map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn request_diagnostics(state: tauri::State<'_, InstallUiInfo>) -> Result<(), String> {
+    let (Some(request), Some(result)) = (
+        state.diagnose_file.as_ref(),
+        state.diagnose_result_file.as_ref(),
+    ) else {
+        return Err("diagnostics are not available".to_string());
+    };
+    let _ = std::fs::remove_file(result);
+    std::fs::write(request, "diagnose").map_err(|err| err.to_string())
+}
+
+/// `{"path": ...}` or `{"error": ...}` once the installer has written the bundle.
+#[tauri::command]
+fn read_diagnostics_result(
+    state: tauri::State<'_, InstallUiInfo>,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(path) = state.diagnose_result_file.as_ref() else {
+        return Ok(None);
+    };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    Ok(serde_json::from_str(&contents).ok())
+}
+
+/// The launcher's crash record shown in "error" mode.
+#[tauri::command]
+fn read_error_report(
+    state: tauri::State<'_, InstallUiInfo>,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(path) = state.error_file.as_ref() else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn open_log_folder(state: tauri::State<'_, InstallUiInfo>) -> Result<(), String> {
+    let Some(dir) = state.log_dir.as_ref() else {
+        return Err("no log folder".to_string());
+    };
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(dir)
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
 #[derive(Serialize)]
 struct LogChunk {
     text: String,
@@ -84,13 +215,41 @@ fn read_install_log(
     if to_read > 0 {
         file.read_exact(&mut buf).map_err(|err| err.to_string())?;
     }
-    let text = String::from_utf8_lossy(&buf).to_string();
+    // Only hand back whole records; a partial trailing line is read again on the next poll.
+    let consumed = match buf.iter().rposition(|b| *b == b'\n') {
+        Some(pos) => pos + 1,
+        None if to_read as u64 == max_bytes => buf.len(),
+        None => 0,
+    };
+    let text = String::from_utf8_lossy(&buf[..consumed])
+        .lines()
+        .map(format_log_record)
+        .map(|line| line + "\n")
+        .collect();
     Ok(LogChunk {
         text,
-        next_offset: safe_offset + buf.len() as u64,
+        next_offset: safe_offset + consumed as u64,
     })
 }
 
+/// Renders one JSON-lines installer record as `HH:MM:SS [level] message`.
+fn format_log_record(line: &str) -> String {
+    let Ok(record) = serde_json::from_str::<serde_json::Value>(line) else {
+        return line.to_string();
+    };
+    let message = record["message"].as_str().unwrap_or_default();
+    let time = record["ts"]
+        .as_str()
+        .and_then(|ts| ts.split('T').nth(1))
+        .map(|t| t.get(..8).unwrap_or(t))
+        .unwrap_or_default();
+    match record["level"].as_str() {
+        Some("warn") => format!("{time} [warn] {message}"),
+        Some("error") => format!("{time} [error] {message}"),
+        _ => format!("{time} {message}"),
+    }
+}
+
 #[tauri::command]
 fn close_window(app: tauri::AppHandle) -> Result<(), String> {
     let Some(window) = app.get_webview_window("main") else {
@@ -120,6 +279,15 @@ fn parse_args() -> InstallUiInfo {
     let mut mode = "install".to_string();
     let mut log_file = None;
     let mut launch_file = None;
+    let mut preflight_file = None;
+    let mut close_file = None;
+    let mut close_decision_file = None;
+    let mut cancel_file = None;
+    let mut progress_file = None;
+    let mut diagnose_file = None;
+    let mut diagnose_result_file = None;
+    let mut error_file = None;
+    let mut log_dir = None;
     let mut args = std::env::args().skip(1);
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -172,6 +340,69 @@ fn parse_args() -> InstallUiInfo {
                     }
                 }
             }
+            "--preflight-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        preflight_file = Some(value);
+                    }
+                }
+            }
+            "--close-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        close_file = Some(value);
+                    }
+                }
+            }
+            "--close-decision-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        close_decision_file = Some(value);
+                    }
+                }
+            }
+            "--cancel-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        cancel_file = Some(value);
+                    }
+                }
+            }
+            "--progress-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        progress_file = Some(value);
+                    }
+                }
+            }
+            "--diagnose-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        diagnose_file = Some(value);
+                    }
+                }
+            }
+            "--diagnose-result-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        diagnose_result_file = Some(value);
+                    }
+                }
+            }
+            "--error-file" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        error_file = Some(value);
+                    }
+                }
+            }
+            "--log-dir" => {
+                if let Some(value) = args.next() {
+                    if !value.trim().is_empty() {
+                        log_dir = Some(value);
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -183,6 +414,15 @@ fn parse_args() -> InstallUiInfo {
         mode,
         log_file,
         launch_file,
+        preflight_file,
+        close_file,
+        close_decision_file,
+        cancel_file,
+        progress_file,
+        diagnose_file,
+        diagnose_result_file,
+        error_file,
+        log_dir,
     }
 }
 
@@ -193,7 +433,12 @@ pub fn run() {
         .manage(info.clone())
         .setup(move |app| {
             if let Some(window) = app.get_webview_window("main") {
-                let _ = window.set_title(&format!("Installing {}", info.name));
+                let title = if info.mode == "error" {
+                    format!("{} stopped", info.name)
+                } else {
+                    format!("Installing {}", info.name)
+                };
+                let _ = window.set_title(&title);
                 if let Some(icon_path) = info.icon_path.as_ref() {
                     if let Ok(image) = tauri::image::Image::from_path(icon_path) {
                         let _ = window.set_icon(image);
@@ -206,6 +451,15 @@ pub fn run() {
             get_install_ui_info,
             get_install_status,
             read_install_log,
+            read_preflight_report,
+            read_close_status,
+            resolve_close_prompt,
+            request_cancel,
+            read_install_progress,
+            request_diagnostics,
+            read_diagnostics_result,
+            read_error_report,
+            open_log_folder,
             mark_launch_requested,
             focus_window,
             close_window